@@ -0,0 +1,51 @@
+//! Derive macros for [bevy_mod_physx](https://docs.rs/bevy_mod_physx),
+//! re-exported from there with `derive` feature.
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
+
+/// Implement `PhysicsLayer` for a fieldless enum.
+///
+/// Each variant is a layer, `1 << index` in declaration order (explicit
+/// discriminants are ignored), so the enum can have at most 32 variants.
+#[proc_macro_derive(PhysicsLayer)]
+pub fn derive_physics_layer(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let Data::Enum(data) = &input.data else {
+        return syn::Error::new_spanned(name, "PhysicsLayer can only be derived for enums")
+            .to_compile_error()
+            .into();
+    };
+
+    if data.variants.len() > 32 {
+        return syn::Error::new_spanned(name, "PhysicsLayer enum can't have more than 32 variants")
+            .to_compile_error()
+            .into();
+    }
+
+    let mut arms = vec![];
+    for (index, variant) in data.variants.iter().enumerate() {
+        if !matches!(variant.fields, Fields::Unit) {
+            return syn::Error::new_spanned(&variant.fields, "PhysicsLayer variants can't have fields")
+                .to_compile_error()
+                .into();
+        }
+
+        let ident = &variant.ident;
+        let index = index as u32;
+        arms.push(quote! { Self::#ident => 1 << #index });
+    }
+
+    quote! {
+        impl #impl_generics ::bevy_mod_physx::plugins::collision_layers::PhysicsLayer for #name #type_generics #where_clause {
+            fn to_bits(&self) -> u32 {
+                match *self {
+                    #(#arms,)*
+                }
+            }
+        }
+    }.into()
+}
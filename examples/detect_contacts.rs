@@ -1,19 +1,7 @@
 mod common;
 
-use std::sync::mpsc::channel;
-
 use bevy::prelude::*;
 use bevy_mod_physx::prelude::{self as bpx, *};
-use bevy_mod_physx::types::OnCollision;
-use bevy_mod_physx::utils::{get_actor_entity_from_ptr, get_shape_entity_from_ptr};
-use physx::scene::FilterShaderDescriptor;
-use physx_sys::{
-    FilterShaderCallbackInfo,
-    PxContactPairFlags,
-    PxContactPair_extractContacts,
-    PxFilterFlags,
-    PxPairFlags,
-};
 
 #[derive(Resource)]
 struct DemoMaterials {
@@ -28,95 +16,15 @@ struct Highlightable;
 #[component(storage = "SparseSet")]
 struct Highlighted;
 
-unsafe extern "C" fn simulation_filter_shader(s: *mut FilterShaderCallbackInfo) -> PxFilterFlags {
-    let s = &mut *s as &mut FilterShaderCallbackInfo;
-    let pair_flags = &mut *(s.pairFlags) as &mut PxPairFlags;
-
-    *pair_flags = PxPairFlags::SolveContact |
-        PxPairFlags::DetectDiscreteContact |
-        PxPairFlags::NotifyTouchFound |
-        PxPairFlags::NotifyTouchLost |
-        PxPairFlags::NotifyContactPoints;
-
-    PxFilterFlags::empty()
-}
-
-#[derive(Event)]
-pub struct CollisionEvent {
-    actor0: Entity,
-    actor1: Entity,
-}
-
 fn main() {
-    let (mpsc_sender, mpsc_receiver) = channel();
-
-    let on_collision = OnCollision::new(move |header, pairs| {
-        assert!(!header.flags.contains(physx_sys::PxContactPairHeaderFlags::RemovedActor0));
-        let actor0 = unsafe { get_actor_entity_from_ptr(header.actors[0] as *const _) };
-
-        assert!(!header.flags.contains(physx_sys::PxContactPairHeaderFlags::RemovedActor1));
-        let actor1 = unsafe { get_actor_entity_from_ptr(header.actors[1] as *const _) };
-
-        for pair in pairs {
-            // this example shows how to extract contact details
-            assert!(!pair.flags.contains(PxContactPairFlags::RemovedShape0));
-            let shape0 = unsafe { get_shape_entity_from_ptr(pair.shapes[0] as *const _) };
-
-            assert!(!pair.flags.contains(PxContactPairFlags::RemovedShape0));
-            let shape1 = unsafe { get_shape_entity_from_ptr(pair.shapes[1] as *const _) };
-
-            let contacts = unsafe {
-                let mut buffer = Vec::with_capacity(pairs.len());
-                PxContactPair_extractContacts(pair, buffer.as_mut_ptr(), pairs.len() as u32);
-                buffer.set_len(pairs.len());
-                buffer
-            };
-
-            let mut status = "Contact";
-            if pair.flags.contains(PxContactPairFlags::ActorPairHasFirstTouch) {
-                status = "New contact";
-            }
-            if pair.flags.contains(PxContactPairFlags::ActorPairLostTouch) {
-                status = "Lost contact";
-            }
-
-            println!("{status} between {shape0:?} and {shape1:?}:");
-            for contact in contacts {
-                println!(
-                    "position: {:?}, separation: {:?}, normal: {:?}, impulse: {:?}",
-                    contact.position.to_bevy(),
-                    contact.separation,
-                    contact.normal.to_bevy(),
-                    contact.impulse.to_bevy(),
-                );
-            }
-            println!("----------");
-        }
-
-        // this callback must not do anything with the scene,
-        // we need to extract required data and send it through a channel into
-        // a system that handles things
-        mpsc_sender.send(CollisionEvent { actor0, actor1 }).unwrap();
-    });
-
     App::new()
         .add_plugins(DefaultPlugins)
         .add_plugins(PhysicsPlugins.set(
-            PhysicsCore {
-                scene: bpx::SceneDescriptor {
-                    // simulation filter shader will filter details that we get in on_collision callback,
-                    // by default on_collision callback doesn't do anything
-                    simulation_filter_shader: FilterShaderDescriptor::CallDefaultFirst(simulation_filter_shader),
-
-                    // callback is a closure, where we pass Sender to
-                    on_collision: Some(on_collision),
-                    ..default()
-                },
-                ..default()
-            }.with_pvd()
+            PhysicsCore::new().with_pvd()
         ))
+        // installs filter shader and collision callback, and sends collision events
+        .add_plugins(CollisionEventsPlugin)
         .add_plugins(common::DemoUtils) // optional
-        .add_physics_event_channel(mpsc_receiver)
         .add_systems(Startup, (
             init_materials,
             ApplyDeferred,
@@ -127,7 +35,10 @@ fn main() {
                 spawn_camera_and_light,
             ),
         ).chain())
-        .add_systems(Update, highlight_on_hit)
+        .add_systems(Update, (
+            print_contacts,
+            highlight_on_hit,
+        ))
         .run();
 }
 
@@ -239,6 +150,11 @@ fn spawn_dynamic(
             ..default()
         })
         .insert(Velocity::linear(Vec3::new(2.5, -5., -10.)))
+        // only report contacts involving the ball
+        .insert(ContactEvents {
+            contact_points: true,
+            ..default()
+        })
         .insert(Name::new("Ball"));
 }
 
@@ -264,10 +180,34 @@ fn spawn_camera_and_light(mut commands: Commands) {
 }
 
 
+fn print_contacts(
+    mut started: EventReader<CollisionStarted>,
+    mut ended: EventReader<CollisionEnded>,
+) {
+    for event in started.read() {
+        // this example shows how to get contact details
+        println!("New contact between {:?} and {:?}:", event.shape0, event.shape1);
+        for contact in event.contacts.iter() {
+            println!(
+                "position: {:?}, separation: {:?}, normal: {:?}, impulse: {:?}",
+                contact.position,
+                contact.separation,
+                contact.normal,
+                contact.impulse,
+            );
+        }
+        println!("----------");
+    }
+
+    for event in ended.read() {
+        println!("Lost contact between {:?} and {:?}", event.shape0, event.shape1);
+    }
+}
+
 fn highlight_on_hit(
     mut commands: Commands,
     materials: Res<DemoMaterials>,
-    mut events: EventReader<CollisionEvent>,
+    mut events: EventReader<CollisionStarted>,
     highlighable: Query<(), With<Highlightable>>,
 ) {
     for event in events.read() {
@@ -1,14 +1,11 @@
 mod common;
 
 use std::f32::consts::FRAC_PI_2;
-use std::ffi::c_void;
 
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 use bevy_mod_physx::prelude::{self as bpx, *};
-use bevy_mod_physx::utils::get_actor_entity_from_ptr;
-use bevy_mod_physx::utils::raycast::SceneQueryFilter;
-use physx_sys::{PxFilterData, PxQueryHitType, PxRigidActor, PxShape};
+use bevy_mod_physx::utils::raycast::{QueryHitType, SceneQueryFilter};
 
 #[derive(Component)]
 struct Surface;
@@ -183,22 +180,13 @@ fn move_kinematic(
     for (camera, camera_transform) in &cameras {
         let Some(ray) = camera.viewport_to_world(camera_transform, cursor_position).ok() else { continue; };
 
-        unsafe extern "C" fn raycast_filter(
-            actor: *const PxRigidActor,
-            _data: *const PxFilterData,
-            _shape: *const PxShape,
-            _hit_flags: u32,
-            user_data: *const c_void,
-        ) -> PxQueryHitType {
-            let entity = *(user_data as *const Entity);
-            if get_actor_entity_from_ptr(actor) == entity {
-                PxQueryHitType::Block
+        let filter = SceneQueryFilter::with_predicate(move |actor, _shape, _hit| {
+            if actor == surface_entity {
+                QueryHitType::Block
             } else {
-                PxQueryHitType::None
+                QueryHitType::None
             }
-        }
-
-        let filter = SceneQueryFilter::callback(raycast_filter, &surface_entity as *const Entity as *mut c_void);
+        });
 
         if let Some(hit) = scene.raycast(ray, f32::MAX, &filter) {
             kinematic.target.translation.x = hit.position.x;
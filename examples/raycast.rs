@@ -139,7 +139,7 @@ fn hover_reset(
 fn hover_highlight(
     mut commands: Commands,
     materials: Res<DemoMaterials>,
-    scene: Res<bpx::Scene>,
+    spatial_query: SpatialQuery,
     window: Query<&Window, With<PrimaryWindow>>,
     cameras: Query<(&Camera, &GlobalTransform)>,
     highlighable: Query<(), With<Highlightable>>,
@@ -150,7 +150,7 @@ fn hover_highlight(
     for (camera, camera_transform) in &cameras {
         let Some(ray) = camera.viewport_to_world(camera_transform, cursor_position).ok() else { continue; };
 
-        if let Some(hit) = scene.raycast(ray, f32::MAX, &default()) {
+        if let Some(hit) = spatial_query.raycast(ray, f32::MAX, &default()) {
             if highlighable.get(hit.actor).is_ok() {
                 commands.entity(hit.actor)
                     .insert(MeshMaterial3d::from(materials.highlighted.clone()))
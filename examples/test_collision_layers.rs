@@ -0,0 +1,106 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_mod_physx::prelude::{self as bpx, *};
+use physx::prelude::*;
+
+#[derive(Clone, Copy, PhysicsLayer)]
+enum Layer {
+    Ground,
+    Player,
+    Enemy,
+}
+
+fn main() {
+    // expected: red balls (players) fall through each other but land on blue ones (enemies),
+    // blue balls land on each other; everything lands on the ground
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PhysicsPlugins.set(
+            PhysicsCore::new().with_pvd()
+        ))
+        .add_plugins(CollisionLayersPlugin)
+        .add_plugins(common::DemoUtils) // optional
+        .add_systems(Startup, (
+            spawn_scene,
+            spawn_camera_and_light,
+        ))
+        .run();
+}
+
+pub fn spawn_scene(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut physics: ResMut<Physics>,
+    mut px_geometries: ResMut<Assets<bpx::Geometry>>,
+    mut px_materials: ResMut<Assets<bpx::Material>>,
+) {
+    let px_material = px_materials.add(bpx::Material::new(&mut physics, 0.5, 0.5, 0.));
+    let player_material = materials.add(Color::srgb(0.9, 0.3, 0.3));
+    let enemy_material = materials.add(Color::srgb(0.3, 0.4, 0.9));
+
+    // plane
+    let primitive = Plane3d::default();
+    commands.spawn((
+        RigidBody::Static,
+        bpx::Shape {
+            geometry: px_geometries.add(primitive),
+            material: px_material.clone(),
+            ..default()
+        },
+        CollisionLayers::new(Layer::Ground, u32::MAX),
+        Mesh3d::from(meshes.add(primitive.mesh().size(1000., 1000.))),
+        MeshMaterial3d::from(materials.add(StandardMaterial::from(Color::srgb(0.3, 0.5, 0.3)))),
+    ));
+
+    let primitive = Sphere::new(0.4);
+    let geometry = px_geometries.add(primitive);
+    let mesh = meshes.add(primitive);
+
+    let player = CollisionLayers::from_layers([ Layer::Player ], [ Layer::Ground, Layer::Enemy ]);
+    let enemy = CollisionLayers::from_layers([ Layer::Enemy ], [ Layer::Ground, Layer::Player, Layer::Enemy ]);
+
+    for (x, bottom, top) in [
+        (-3., (player, &player_material), (player, &player_material)),
+        (-1., (enemy, &enemy_material), (player, &player_material)),
+        (1., (player, &player_material), (enemy, &enemy_material)),
+        (3., (enemy, &enemy_material), (enemy, &enemy_material)),
+    ] {
+        for (y, (layers, material)) in [ (1., bottom), (3., top) ] {
+            commands.spawn((
+                RigidBody::Dynamic,
+                bpx::Shape {
+                    geometry: geometry.clone(),
+                    material: px_material.clone(),
+                    ..default()
+                },
+                layers,
+                Mesh3d::from(mesh.clone()),
+                MeshMaterial3d::from(material.clone()),
+                Transform::from_xyz(x, y, 0.),
+            ));
+        }
+    }
+}
+
+fn spawn_camera_and_light(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Camera"),
+            Transform::from_xyz(0., 1., 0.),
+            Visibility::default(),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Camera3d::default(),
+                Transform::from_xyz(0.0, 2., 10.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ));
+        });
+
+    commands.spawn((
+        Name::new("Light"),
+        DirectionalLight::default(),
+        Transform::from_rotation(Quat::from_rotation_x(-std::f32::consts::FRAC_PI_4)),
+    ));
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_mod_physx::prelude::{self as bpx, *};
+use physx::prelude::*;
+
+fn main() {
+    // expected (left to right):
+    //  - door swinging on a hinge attached to the world, within limits;
+    //  - chain of boxes hanging from the world on spherical joints;
+    //  - box sliding along a rail, stopped by the limits;
+    //  - ball on a rope (distance joint) swinging from a static post;
+    //  - stack of two boxes glued by a fixed joint, breaking when the ball hits it;
+    //  - in the back, a spinning box bouncing on a soft D6 "suspension"
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PhysicsPlugins.set(
+            PhysicsCore::new().with_pvd()
+        ))
+        .add_plugins(common::DemoUtils) // optional
+        .add_systems(Startup, (
+            spawn_scene,
+            spawn_camera_and_light,
+        ))
+        .add_systems(Update, log_broken_joints)
+        .run();
+}
+
+pub fn spawn_scene(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut physics: ResMut<Physics>,
+    mut px_geometries: ResMut<Assets<bpx::Geometry>>,
+    mut px_materials: ResMut<Assets<bpx::Material>>,
+) {
+    let px_material = px_materials.add(bpx::Material::new(&mut physics, 0.5, 0.5, 0.));
+    let body_material = materials.add(Color::srgb(0.8, 0.7, 0.6));
+
+    // plane
+    let primitive = Plane3d::default();
+    commands.spawn((
+        RigidBody::Static,
+        bpx::Shape {
+            geometry: px_geometries.add(primitive),
+            material: px_material.clone(),
+            ..default()
+        },
+        Mesh3d::from(meshes.add(primitive.mesh().size(1000., 1000.))),
+        MeshMaterial3d::from(materials.add(StandardMaterial::from(Color::srgb(0.3, 0.5, 0.3)))),
+    ));
+
+    let mut spawn_body = |commands: &mut Commands, primitive: Cuboid, transform: Transform, body: RigidBody| {
+        commands.spawn((
+            body,
+            bpx::Shape {
+                geometry: px_geometries.add(primitive),
+                material: px_material.clone(),
+                ..default()
+            },
+            Mesh3d::from(meshes.add(primitive)),
+            MeshMaterial3d::from(body_material.clone()),
+            transform,
+        )).id()
+    };
+
+    // door, hinge is along Y axis at its left edge
+    let door = spawn_body(&mut commands, Cuboid::new(1.5, 2., 0.1), Transform::from_xyz(-6., 1.1, 0.), RigidBody::Dynamic);
+    let hinge = Quat::from_rotation_z(std::f32::consts::FRAC_PI_2);
+    commands.spawn((
+        Joint::new(None, Some(door)).with_frames(
+            Transform::from_xyz(-6.75, 1.1, 0.).with_rotation(hinge),
+            Transform::from_xyz(-0.75, 0., 0.).with_rotation(hinge),
+        ),
+        RevoluteJoint {
+            limit: Some(JointLimit::new(-1.5, 1.5)),
+            drive_velocity: Some(1.),
+            drive_force_limit: 10.,
+            ..default()
+        },
+    ));
+
+    // chain
+    let mut parent = None;
+    for i in 0..4 {
+        let y = 5. - i as f32 * 0.6;
+        let link = spawn_body(&mut commands, Cuboid::new(0.3, 0.5, 0.3), Transform::from_xyz(-3., y, 0.), RigidBody::Dynamic);
+        let frame_a = if parent.is_some() { Transform::from_xyz(0., -0.3, 0.) } else { Transform::from_xyz(-3., y + 0.3, 0.) };
+        commands.spawn((
+            Joint::new(parent, Some(link)).with_frames(frame_a, Transform::from_xyz(0., 0.3, 0.)),
+            SphericalJoint { limit: Some(JointLimitCone::new(0.5, 0.5)) },
+        ));
+        parent = Some(link);
+    }
+
+    // slider along X axis
+    let slider = spawn_body(&mut commands, Cuboid::new(0.5, 0.5, 0.5), Transform::from_xyz(0., 3., 0.), RigidBody::Dynamic);
+    commands.spawn((
+        Joint::new(None, Some(slider)).with_frames(
+            Transform::from_xyz(0., 3., 0.).with_rotation(Quat::from_rotation_z(-0.5)),
+            Transform::from_rotation(Quat::from_rotation_z(-0.5)),
+        ),
+        PrismaticJoint { limit: Some(JointLimit::new(-1., 1.)) },
+    ));
+
+    // rope, attached to the side of the post, so the ball doesn't hit it
+    let post = spawn_body(&mut commands, Cuboid::new(0.2, 4., 0.2), Transform::from_xyz(4., 2., -1.), RigidBody::Static);
+    let ball = spawn_body(&mut commands, Cuboid::new(0.4, 0.4, 0.4), Transform::from_xyz(7.5, 4., 0.), RigidBody::Dynamic);
+    commands.spawn((
+        Joint::new(Some(post), Some(ball)).with_frames(Transform::from_xyz(0., 2., 1.), default()),
+        DistanceJoint { max_distance: Some(3.5), ..default() },
+    ));
+
+    // breakable stack, hit by the ball on the rope
+    let bottom = spawn_body(&mut commands, Cuboid::new(0.5, 0.5, 0.5), Transform::from_xyz(2.5, 0.25, 0.), RigidBody::Dynamic);
+    let top = spawn_body(&mut commands, Cuboid::new(0.5, 0.5, 0.5), Transform::from_xyz(2.5, 0.75, 0.), RigidBody::Dynamic);
+    commands.spawn((
+        Joint::new(Some(bottom), Some(top))
+            .with_frames(Transform::from_xyz(0., 0.25, 0.), Transform::from_xyz(0., -0.25, 0.))
+            .with_break_force(20., 20.),
+        FixedJoint,
+    ));
+
+    // suspension, moves along Y axis on a spring, and spins around it with a drive
+    let wheel = spawn_body(&mut commands, Cuboid::new(1., 0.3, 1.), Transform::from_xyz(0., 3., -4.), RigidBody::Dynamic);
+    let up = Quat::from_rotation_z(std::f32::consts::FRAC_PI_2);
+    commands.spawn((
+        Joint::new(None, Some(wheel)).with_frames(
+            Transform::from_xyz(0., 3., -4.).with_rotation(up),
+            Transform::from_rotation(up),
+        ),
+        D6Joint {
+            x: D6JointMotion::Limited(JointLimit::new(-0.5, 0.5).with_spring(200., 5.)),
+            twist: D6JointMotion::Free,
+            ..default()
+        },
+        D6JointDrives {
+            twist: D6JointDrive::new(0., 10.),
+            ..default()
+        },
+        D6JointDriveVelocity { angular: Vec3::new(3., 0., 0.), ..default() },
+    ));
+}
+
+fn log_broken_joints(mut events: EventReader<ConstraintBroken>) {
+    for event in events.read() {
+        info!("joint broken: {event:?}");
+    }
+}
+
+fn spawn_camera_and_light(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Camera"),
+            Transform::from_xyz(0., 2.5, 0.),
+            Visibility::default(),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Camera3d::default(),
+                Transform::from_xyz(0.0, 4., 16.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ));
+        });
+
+    commands.spawn((
+        Name::new("Light"),
+        DirectionalLight::default(),
+        Transform::from_rotation(Quat::from_rotation_x(-std::f32::consts::FRAC_PI_4)),
+    ));
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_mod_physx::prelude::{self as bpx, *};
+use physx::prelude::*;
+
+fn main() {
+    // expected: everything launched from below passes through the platforms
+    // and lands on top of them, everything dropped from above lands right away
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PhysicsPlugins.set(
+            PhysicsCore::new().with_pvd()
+        ))
+        .add_plugins(OneWayPlatformPlugin)
+        .add_plugins(common::DemoUtils) // optional
+        .add_systems(Startup, (
+            spawn_scene,
+            spawn_camera_and_light,
+        ))
+        .run();
+}
+
+pub fn spawn_scene(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut physics: ResMut<Physics>,
+    mut px_geometries: ResMut<Assets<bpx::Geometry>>,
+    mut px_materials: ResMut<Assets<bpx::Material>>,
+) {
+    let px_material = px_materials.add(bpx::Material::new(&mut physics, 0.5, 0.5, 0.));
+    let body_material = materials.add(Color::srgb(0.8, 0.7, 0.6));
+    let platform_material = materials.add(Color::srgba(0.3, 0.4, 0.9, 0.6));
+
+    // plane
+    let primitive = Plane3d::default();
+    commands.spawn((
+        RigidBody::Static,
+        bpx::Shape {
+            geometry: px_geometries.add(primitive),
+            material: px_material.clone(),
+            ..default()
+        },
+        Mesh3d::from(meshes.add(primitive.mesh().size(1000., 1000.))),
+        MeshMaterial3d::from(materials.add(StandardMaterial::from(Color::srgb(0.3, 0.5, 0.3)))),
+    ));
+
+    // flat platform, and a tilted one
+    let primitive = Cuboid::new(6., 0.2, 3.);
+    for transform in [
+        Transform::from_xyz(-4., 3., 0.),
+        Transform::from_xyz(4., 3., 0.).with_rotation(Quat::from_rotation_z(0.3)),
+    ] {
+        commands.spawn((
+            RigidBody::Static,
+            bpx::Shape {
+                geometry: px_geometries.add(primitive),
+                material: px_material.clone(),
+                ..default()
+            },
+            OneWayPlatform { normal: Dir3::Y },
+            Mesh3d::from(meshes.add(primitive)),
+            MeshMaterial3d::from(platform_material.clone()),
+            transform,
+        ));
+    }
+
+    let primitive = Sphere::new(0.4);
+    let geometry = px_geometries.add(primitive);
+    let mesh = meshes.add(primitive);
+
+    for x in [ -4., 4. ] {
+        // dynamic body launched from below
+        commands.spawn((
+            RigidBody::Dynamic,
+            bpx::Shape {
+                geometry: geometry.clone(),
+                material: px_material.clone(),
+                ..default()
+            },
+            Mesh3d::from(mesh.clone()),
+            MeshMaterial3d::from(body_material.clone()),
+            Transform::from_xyz(x - 1.5, 0.5, 0.),
+            Velocity::linear(Vec3::new(0., 10., 0.)),
+        ));
+
+        // dynamic body dropped from above
+        commands.spawn((
+            RigidBody::Dynamic,
+            bpx::Shape {
+                geometry: geometry.clone(),
+                material: px_material.clone(),
+                ..default()
+            },
+            Mesh3d::from(mesh.clone()),
+            MeshMaterial3d::from(body_material.clone()),
+            Transform::from_xyz(x, 6., 0.),
+        ));
+
+        // articulation with two links launched from below
+        let root = commands.spawn((
+            RigidBody::ArticulationLink,
+            ArticulationRoot::default(),
+            bpx::Shape {
+                geometry: geometry.clone(),
+                material: px_material.clone(),
+                ..default()
+            },
+            Mesh3d::from(mesh.clone()),
+            MeshMaterial3d::from(body_material.clone()),
+            Transform::from_xyz(x + 1.5, 0.5, 0.),
+            Velocity::linear(Vec3::new(0., 12., 0.)),
+        )).id();
+
+        commands.spawn((
+            RigidBody::ArticulationLink,
+            ArticulationJoint {
+                parent: root,
+                parent_pose: Transform::from_xyz(0., 0., 0.5),
+                child_pose: Transform::from_xyz(0., 0., -0.5),
+                joint_type: ArticulationJointType::Spherical,
+                motion_swing1: ArticulationJointMotion::Free,
+                motion_swing2: ArticulationJointMotion::Free,
+                motion_twist: ArticulationJointMotion::Free,
+                ..default()
+            },
+            bpx::Shape {
+                geometry: geometry.clone(),
+                material: px_material.clone(),
+                ..default()
+            },
+            Mesh3d::from(mesh.clone()),
+            MeshMaterial3d::from(body_material.clone()),
+            Transform::from_xyz(x + 1.5, 0.5, 1.),
+        ));
+    }
+}
+
+fn spawn_camera_and_light(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Camera"),
+            Transform::from_xyz(0., 2.5, 0.),
+            Visibility::default(),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Camera3d::default(),
+                Transform::from_xyz(0.0, 4., 16.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ));
+        });
+
+    commands.spawn((
+        Name::new("Light"),
+        DirectionalLight::default(),
+        Transform::from_rotation(Quat::from_rotation_x(-std::f32::consts::FRAC_PI_4)),
+    ));
+}
@@ -0,0 +1,319 @@
+//! Contact modification, allows changing contacts before they are passed to the solver.
+//!
+//! Register a [ContactModifier] with [PhysicsCore::with_contact_modifier](crate::PhysicsCore::with_contact_modifier),
+//! and add [ModifyContacts](crate::plugins::modify_contacts::ModifyContacts) component to actors
+//! or shapes whose contacts you want to modify. Modifiers are only called for pairs where
+//! at least one of the shapes requests it, and require crate filter shader (see [crate::core::filter]).
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_mod_physx::prelude::*;
+//! // make everything bouncy
+//! let core = PhysicsCore::new().with_contact_modifier(|pair: &mut ContactModifyPair| {
+//!     for mut contact in pair.contacts() {
+//!         contact.set_restitution(1.);
+//!     }
+//! });
+//! ```
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use physx::prelude::ConcreteType;
+use physx_sys::{
+    PxBase_getConcreteType,
+    PxContactModifyCallback,
+    PxContactModifyPair,
+    PxContactSet,
+    PxContactSet_getDynamicFriction,
+    PxContactSet_getMaxImpulse,
+    PxContactSet_getNormal,
+    PxContactSet_getPoint,
+    PxContactSet_getRestitution,
+    PxContactSet_getSeparation,
+    PxContactSet_getStaticFriction,
+    PxContactSet_getTargetVelocity,
+    PxContactSet_ignore_mut,
+    PxContactSet_setDynamicFriction_mut,
+    PxContactSet_setInvInertiaScale0_mut,
+    PxContactSet_setInvInertiaScale1_mut,
+    PxContactSet_setInvMassScale0_mut,
+    PxContactSet_setInvMassScale1_mut,
+    PxContactSet_setMaxImpulse_mut,
+    PxContactSet_setNormal_mut,
+    PxContactSet_setPoint_mut,
+    PxContactSet_setRestitution_mut,
+    PxContactSet_setSeparation_mut,
+    PxContactSet_setStaticFriction_mut,
+    PxContactSet_setTargetVelocity_mut,
+    PxContactSet_size,
+    PxRigidActor,
+    PxRigidBody,
+    PxRigidBody_getLinearVelocity,
+};
+
+use crate::prelude::*;
+use crate::utils::{get_actor_entity_from_ptr, get_shape_entity_from_ptr};
+
+/// Modifies contacts between pairs of shapes before they are passed to the solver.
+///
+/// It is called from PhysX worker threads during simulation (possibly from multiple
+/// threads at once), so it can't access bevy world. Any data it needs should be
+/// shared through `Arc` and updated by a system.
+///
+/// Implemented for closures taking [ContactModifyPair].
+pub trait ContactModifier: Send + Sync + 'static {
+    fn modify_contacts(&self, pair: &mut ContactModifyPair);
+}
+
+impl<F: Fn(&mut ContactModifyPair) + Send + Sync + 'static> ContactModifier for F {
+    fn modify_contacts(&self, pair: &mut ContactModifyPair) {
+        self(pair)
+    }
+}
+
+/// A pair of touching shapes with a set of modifiable contact points.
+///
+/// Contact normals point from the second shape to the first one.
+pub struct ContactModifyPair<'a> {
+    pair: &'a mut PxContactModifyPair,
+}
+
+impl ContactModifyPair<'_> {
+    pub fn actor0(&self) -> Entity {
+        // SAFETY: actors are created by this crate, and alive during simulation
+        unsafe { get_actor_entity_from_ptr(self.pair.actor[0]) }
+    }
+
+    pub fn actor1(&self) -> Entity {
+        // SAFETY: actors are created by this crate, and alive during simulation
+        unsafe { get_actor_entity_from_ptr(self.pair.actor[1]) }
+    }
+
+    pub fn shape0(&self) -> Entity {
+        // SAFETY: shapes are created by this crate, and alive during simulation
+        unsafe { get_shape_entity_from_ptr(self.pair.shape[0]) }
+    }
+
+    pub fn shape1(&self) -> Entity {
+        // SAFETY: shapes are created by this crate, and alive during simulation
+        unsafe { get_shape_entity_from_ptr(self.pair.shape[1]) }
+    }
+
+    /// World transform of the first actor.
+    pub fn transform0(&self) -> Transform {
+        self.pair.transform[0].to_bevy()
+    }
+
+    /// World transform of the second actor.
+    pub fn transform1(&self) -> Transform {
+        self.pair.transform[1].to_bevy()
+    }
+
+    /// Linear velocity of the first actor at the start of the simulation step
+    /// (zero for static actors).
+    pub fn linear_velocity0(&self) -> Vec3 {
+        // SAFETY: actors are alive during simulation
+        unsafe { linear_velocity(self.pair.actor[0]) }
+    }
+
+    /// Linear velocity of the second actor at the start of the simulation step
+    /// (zero for static actors).
+    pub fn linear_velocity1(&self) -> Vec3 {
+        // SAFETY: actors are alive during simulation
+        unsafe { linear_velocity(self.pair.actor[1]) }
+    }
+
+    /// Number of contact points.
+    pub fn len(&self) -> usize {
+        unsafe { PxContactSet_size(&self.pair.contacts) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Contact point at `index`, `None` if out of bounds.
+    pub fn contact(&mut self, index: usize) -> Option<ModifiableContact<'_>> {
+        if index >= self.len() { return None; }
+        Some(ModifiableContact { contacts: &mut self.pair.contacts, index: index as u32, _marker: PhantomData })
+    }
+
+    /// All contact points of this pair.
+    pub fn contacts(&mut self) -> impl Iterator<Item = ModifiableContact<'_>> {
+        let contacts: *mut PxContactSet = &mut self.pair.contacts;
+        (0..self.len() as u32).map(move |index| ModifiableContact { contacts, index, _marker: PhantomData })
+    }
+
+    /// Ignore all contact points of this pair.
+    pub fn ignore_all(&mut self) {
+        for mut contact in self.contacts() {
+            contact.ignore();
+        }
+    }
+
+    /// Scale inverse mass and inverse inertia of both actors, as seen by the solver
+    /// for this pair only (e.g. zero on one side makes it behave as infinitely heavy).
+    pub fn set_inv_mass_scales(&mut self, inv_mass0: f32, inv_inertia0: f32, inv_mass1: f32, inv_inertia1: f32) {
+        unsafe {
+            PxContactSet_setInvMassScale0_mut(&mut self.pair.contacts, inv_mass0);
+            PxContactSet_setInvInertiaScale0_mut(&mut self.pair.contacts, inv_inertia0);
+            PxContactSet_setInvMassScale1_mut(&mut self.pair.contacts, inv_mass1);
+            PxContactSet_setInvInertiaScale1_mut(&mut self.pair.contacts, inv_inertia1);
+        }
+    }
+}
+
+unsafe fn linear_velocity(actor: *const PxRigidActor) -> Vec3 {
+    let actor_type = ConcreteType::from(unsafe { PxBase_getConcreteType(actor as *const _) });
+    if matches!(actor_type, ConcreteType::RigidStatic) { return Vec3::ZERO; }
+
+    // SAFETY: every other actor in the scene is a rigid body (dynamic or articulation link)
+    unsafe { PxRigidBody_getLinearVelocity(actor as *const PxRigidBody) }.to_bevy()
+}
+
+/// Modifiable contact point of [ContactModifyPair].
+pub struct ModifiableContact<'a> {
+    // each contact only accesses its own index, so several of them can exist at once
+    contacts: *mut PxContactSet,
+    index: u32,
+    _marker: PhantomData<&'a mut PxContactSet>,
+}
+
+impl ModifiableContact<'_> {
+    /// Position of the contact point in world space.
+    pub fn point(&self) -> Vec3 {
+        unsafe { (*PxContactSet_getPoint(self.contacts, self.index)).to_bevy() }
+    }
+
+    pub fn set_point(&mut self, point: Vec3) {
+        unsafe { PxContactSet_setPoint_mut(self.contacts, self.index, &point.to_physx_sys()) };
+    }
+
+    /// Contact normal, pointing from the second shape to the first one.
+    pub fn normal(&self) -> Vec3 {
+        unsafe { (*PxContactSet_getNormal(self.contacts, self.index)).to_bevy() }
+    }
+
+    /// Set contact normal, it must be normalized.
+    pub fn set_normal(&mut self, normal: Vec3) {
+        unsafe { PxContactSet_setNormal_mut(self.contacts, self.index, &normal.to_physx_sys()) };
+    }
+
+    /// Separation of the shapes at the contact point, negative means penetration.
+    pub fn separation(&self) -> f32 {
+        unsafe { PxContactSet_getSeparation(self.contacts, self.index) }
+    }
+
+    pub fn set_separation(&mut self, separation: f32) {
+        unsafe { PxContactSet_setSeparation_mut(self.contacts, self.index, separation) };
+    }
+
+    /// Target relative velocity of the shapes at the contact point
+    /// (used to emulate moving surfaces like conveyor belts).
+    pub fn target_velocity(&self) -> Vec3 {
+        unsafe { (*PxContactSet_getTargetVelocity(self.contacts, self.index)).to_bevy() }
+    }
+
+    pub fn set_target_velocity(&mut self, velocity: Vec3) {
+        unsafe { PxContactSet_setTargetVelocity_mut(self.contacts, self.index, &velocity.to_physx_sys()) };
+    }
+
+    /// Maximum impulse the solver may apply at the contact point.
+    pub fn max_impulse(&self) -> f32 {
+        unsafe { PxContactSet_getMaxImpulse(self.contacts, self.index) }
+    }
+
+    pub fn set_max_impulse(&mut self, max_impulse: f32) {
+        unsafe { PxContactSet_setMaxImpulse_mut(self.contacts, self.index, max_impulse) };
+    }
+
+    pub fn restitution(&self) -> f32 {
+        unsafe { PxContactSet_getRestitution(self.contacts, self.index) }
+    }
+
+    pub fn set_restitution(&mut self, restitution: f32) {
+        unsafe { PxContactSet_setRestitution_mut(self.contacts, self.index, restitution) };
+    }
+
+    pub fn static_friction(&self) -> f32 {
+        unsafe { PxContactSet_getStaticFriction(self.contacts, self.index) }
+    }
+
+    pub fn set_static_friction(&mut self, friction: f32) {
+        unsafe { PxContactSet_setStaticFriction_mut(self.contacts, self.index, friction) };
+    }
+
+    pub fn dynamic_friction(&self) -> f32 {
+        unsafe { PxContactSet_getDynamicFriction(self.contacts, self.index) }
+    }
+
+    pub fn set_dynamic_friction(&mut self, friction: f32) {
+        unsafe { PxContactSet_setDynamicFriction_mut(self.contacts, self.index, friction) };
+    }
+
+    /// Ignore the contact point (solver won't apply any impulse there).
+    pub fn ignore(&mut self) {
+        unsafe { PxContactSet_ignore_mut(self.contacts, self.index) };
+    }
+}
+
+// PxContactModifyCallback is a pure virtual C++ class not exposed through
+// physx-sys C api, so we construct its vtable manually (see also utils/bvh.rs).
+//
+// onContactModify comes first, followed by a protected virtual destructor, which
+// takes two slots in Itanium ABI and one slot in MSVC ABI. Destructor is never
+// called by physx.
+unsafe extern "C" fn noop_destructor(_this: *mut c_void) {}
+
+#[repr(C)]
+struct ContactModifyCallbackVtable {
+    on_contact_modify: unsafe extern "C" fn(*mut ContactModifyCallback, *mut PxContactModifyPair, u32),
+    #[cfg(not(target_env = "msvc"))]
+    complete_destructor: unsafe extern "C" fn(*mut c_void),
+    deleting_destructor: unsafe extern "C" fn(*mut c_void),
+}
+
+#[repr(C)]
+pub(crate) struct ContactModifyCallback {
+    vtable: *const ContactModifyCallbackVtable,
+    modifiers: Vec<Arc<dyn ContactModifier>>,
+}
+
+// SAFETY: modifiers are Send + Sync, vtable is static
+unsafe impl Send for ContactModifyCallback {}
+unsafe impl Sync for ContactModifyCallback {}
+
+impl ContactModifyCallback {
+    const VTABLE: ContactModifyCallbackVtable = ContactModifyCallbackVtable {
+        on_contact_modify: Self::on_contact_modify,
+        #[cfg(not(target_env = "msvc"))]
+        complete_destructor: noop_destructor,
+        deleting_destructor: noop_destructor,
+    };
+
+    /// Must be boxed, so the pointer passed to physx stays valid.
+    pub(crate) fn new(modifiers: Vec<Arc<dyn ContactModifier>>) -> Box<Self> {
+        Box::new(Self { vtable: &Self::VTABLE, modifiers })
+    }
+
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut PxContactModifyCallback {
+        self as *mut Self as *mut PxContactModifyCallback
+    }
+
+    unsafe extern "C" fn on_contact_modify(this: *mut ContactModifyCallback, pairs: *mut PxContactModifyPair, count: u32) {
+        // SAFETY: physx calls this with the pointer we registered in the scene,
+        // and it may be called from multiple threads, so only shared access here
+        let this = unsafe { &*this };
+        if pairs.is_null() || count == 0 { return; }
+        let pairs = unsafe { std::slice::from_raw_parts_mut(pairs, count as usize) };
+
+        for pair in pairs {
+            let mut pair = ContactModifyPair { pair };
+            for modifier in this.modifiers.iter() {
+                modifier.modify_contacts(&mut pair);
+            }
+        }
+    }
+}
@@ -0,0 +1,763 @@
+//! Simulation filter shader used by plugins of this crate.
+//!
+//! It is installed automatically when a plugin that needs it is added (e.g.
+//! [CollisionEventsPlugin](crate::plugins::collision_events::CollisionEventsPlugin)),
+//! unless a custom shader is set in [SceneDescriptor::simulation_filter_shader](crate::core::scene::SceneDescriptor::simulation_filter_shader).
+//!
+//! Shader reads settings from simulation filter data of each shape. Last word
+//! (`word3`) is reserved for flags set by components of this crate (e.g.
+//! [ContactEvents](crate::plugins::collision_events::ContactEvents)), so it shouldn't
+//! be set through [ShapeFilterData](crate::plugins::shape_filter_data::ShapeFilterData).
+//!
+//! Shapes with [CollisionLayers](crate::plugins::collision_layers::CollisionLayers)
+//! only collide if each shape's memberships match the other's filters, and actors listed
+//! in [CollisionExclusions](crate::plugins::collision_exclusions::CollisionExclusions)
+//! never collide.
+//!
+//! Custom filtering rules can be added with [SimulationFilter] (set in
+//! [SceneDescriptor::simulation_filter](crate::core::scene::SceneDescriptor::simulation_filter)).
+//! It's installed as `PxSimulationFilterCallback` of its scene, so PhysX calls it
+//! for every new pair of shapes the shader didn't reject, with entities of
+//! the shapes and their actors.
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use bevy::ecs::entity::EntityHashSet;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use physx::traits::Class;
+use physx_sys::{
+    phys_PxFilterObjectIsKinematic,
+    phys_PxFilterObjectIsTrigger,
+    FilterShaderCallbackInfo,
+    PxFilterData,
+    PxFilterData_new_2,
+    PxFilterFlags,
+    PxPairFlags,
+    PxScene_resetFiltering_mut_1,
+    PxShape_getActor,
+    PxShape_getSimulationFilterData,
+    PxShape_setSimulationFilterData_mut,
+};
+
+use crate::prelude::{self as bpx, Scene, *};
+use crate::utils::{get_actor_entity_from_ptr, get_shape_entity_from_ptr};
+
+// bits of simulation filter data word3
+pub(crate) const NOTIFY_TOUCH_FOUND: u32 = 1 << 0;
+pub(crate) const NOTIFY_TOUCH_PERSISTS: u32 = 1 << 1;
+pub(crate) const NOTIFY_TOUCH_LOST: u32 = 1 << 2;
+pub(crate) const NOTIFY_CONTACT_POINTS: u32 = 1 << 3;
+pub(crate) const NOTIFY_FORCE_THRESHOLD: u32 = 1 << 4;
+pub(crate) const CONTACT_EVENTS_MASK: u32 = 0b11111;
+pub(crate) const MODIFY_CONTACTS: u32 = 1 << 5;
+// words 0 and 1 hold collision layers (only in simulation filter data)
+pub(crate) const COLLISION_LAYERS: u32 = 1 << 6;
+// upper half holds id of the actor in excluded pairs table (0 if it has no exclusions)
+pub(crate) const EXCLUSION_ID_SHIFT: u32 = 16;
+pub(crate) const EXCLUSION_ID_MASK: u32 = 0xffff << EXCLUSION_ID_SHIFT;
+
+/// Result of [SimulationFilter::pair_found].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FilterResult {
+    /// Process the pair with its pair flags.
+    #[default]
+    Keep,
+    /// Ignore the pair while filter data of its shapes stays the same.
+    ///
+    /// PhysX will filter the pair again when filter data changes, or when
+    /// filtering is reset for its shapes.
+    Suppress,
+    /// Ignore the pair until one of its shapes is removed from the scene.
+    Kill,
+}
+
+/// New pair of shapes, passed to [SimulationFilter::pair_found].
+pub struct FilterPair<'a> {
+    /// Id assigned to the pair by PhysX, same as in [LostPair::id] and [PairStatusChange::id].
+    pub id: u64,
+    /// Actor entities of both shapes.
+    pub actors: [Entity; 2],
+    /// Shape entities.
+    pub shapes: [Entity; 2],
+    attributes: [u32; 2],
+    /// Simulation filter data of both shapes (`word3` is reserved by this crate).
+    pub filter_data: [[u32; 4]; 2],
+    /// Pair flags, initialized by crate filter shader (e.g. with contact reports
+    /// requested by [ContactEvents](crate::plugins::collision_events::ContactEvents)).
+    pub pair_flags: &'a mut PxPairFlags,
+}
+
+impl FilterPair<'_> {
+    /// Whether the shape (0 or 1) is a trigger.
+    pub fn is_trigger(&self, index: usize) -> bool {
+        unsafe { phys_PxFilterObjectIsTrigger(self.attributes[index]) }
+    }
+
+    /// Whether the shape (0 or 1) belongs to a kinematic actor.
+    pub fn is_kinematic(&self, index: usize) -> bool {
+        unsafe { phys_PxFilterObjectIsKinematic(self.attributes[index]) }
+    }
+}
+
+/// Pair kept or suppressed by [SimulationFilter::pair_found] which PhysX no longer tracks,
+/// passed to [SimulationFilter::pair_lost].
+#[derive(Debug, Clone, Copy)]
+pub struct LostPair {
+    pub id: u64,
+    /// Actor entities of both shapes (as they were when the pair was found).
+    pub actors: [Entity; 2],
+    /// Shape entities.
+    pub shapes: [Entity; 2],
+    /// Simulation filter data of both shapes.
+    pub filter_data: [[u32; 4]; 2],
+    /// Whether the pair is lost because one of its shapes was removed from the scene,
+    /// otherwise their bounds stopped overlapping or filtering was reset.
+    pub removed: bool,
+}
+
+/// New status of a pair found earlier, returned from [SimulationFilter::status_change].
+#[derive(Debug, Clone, Copy)]
+pub struct PairStatusChange {
+    /// [FilterPair::id] of the pair.
+    pub id: u64,
+    /// New pair flags (ignored if pair is suppressed or killed).
+    pub pair_flags: PxPairFlags,
+    pub result: FilterResult,
+}
+
+/// Custom collision filtering rules, written in Rust.
+///
+/// It's installed as `PxSimulationFilterCallback` of the scene, so it's called during
+/// simulation and can't access bevy world. Any state it needs (e.g. teams)
+/// should be shared through `Arc` and updated by a system.
+///
+/// Pairs rejected by [CollisionLayers](crate::plugins::collision_layers::CollisionLayers)
+/// or [CollisionExclusions](crate::plugins::collision_exclusions::CollisionExclusions)
+/// are not passed to it.
+///
+/// Implemented for closures taking [FilterPair].
+pub trait SimulationFilter: Send + Sync + 'static {
+    /// Called for every new pair of shapes.
+    fn pair_found(&self, pair: &mut FilterPair) -> FilterResult;
+
+    /// Called when a pair that wasn't killed in [pair_found](Self::pair_found) is lost.
+    fn pair_lost(&self, _pair: &LostPair) {}
+
+    /// Called repeatedly before each simulation step until it returns `None`,
+    /// to change how pairs found earlier are processed (e.g. suppress a pair once
+    /// game state changes, without changing filter data of its shapes).
+    fn status_change(&self) -> Option<PairStatusChange> {
+        None
+    }
+}
+
+impl<F: Fn(&mut FilterPair) -> FilterResult + Send + Sync + 'static> SimulationFilter for F {
+    fn pair_found(&self, pair: &mut FilterPair) -> FilterResult {
+        self(pair)
+    }
+}
+
+impl FilterResult {
+    // PxFilterFlag::eNOTIFY makes physx call pairLost for the pair
+    fn filter_flags(self) -> PxFilterFlags {
+        match self {
+            Self::Keep => PxFilterFlags::Notify,
+            Self::Suppress => PxFilterFlags::Suppress | PxFilterFlags::Notify,
+            Self::Kill => PxFilterFlags::Kill,
+        }
+    }
+}
+
+// number of live scenes with a SimulationFilter, crate shader only asks physx
+// to call filter callbacks while it's non-zero (it's an optimization, every
+// scene created by this crate has a callback that handles a missing filter)
+static SIMULATION_FILTERS: AtomicUsize = AtomicUsize::new(0);
+
+// PxSimulationFilterCallback is a pure virtual C++ class not exposed through
+// physx-sys C api, so we construct its vtable manually (see also contact_modify.rs).
+//
+// pairFound, pairLost and statusChange come in declaration order, followed by
+// a protected virtual destructor, which takes two slots in Itanium ABI and one
+// slot in MSVC ABI. Destructor is never called by physx.
+//
+// PxFilterFlags (PxFlags) has a user-provided copy constructor, so pairFound returns
+// it through a hidden pointer: Itanium ABI passes it before `this`, MSVC ABI
+// passes it after `this`. Both return the same pointer.
+unsafe extern "C" fn noop_destructor(_this: *mut c_void) {}
+
+#[cfg(not(target_env = "msvc"))]
+type PairFoundFn = unsafe extern "C" fn(
+    *mut PxFilterFlags,
+    *mut SimulationFilterCallback,
+    u64,
+    u32, PxFilterData, *const physx_sys::PxActor, *const physx_sys::PxShape,
+    u32, PxFilterData, *const physx_sys::PxActor, *const physx_sys::PxShape,
+    *mut PxPairFlags,
+) -> *mut PxFilterFlags;
+
+#[cfg(target_env = "msvc")]
+type PairFoundFn = unsafe extern "C" fn(
+    *mut SimulationFilterCallback,
+    *mut PxFilterFlags,
+    u64,
+    u32, PxFilterData, *const physx_sys::PxActor, *const physx_sys::PxShape,
+    u32, PxFilterData, *const physx_sys::PxActor, *const physx_sys::PxShape,
+    *mut PxPairFlags,
+) -> *mut PxFilterFlags;
+
+#[repr(C)]
+struct SimulationFilterCallbackVtable {
+    pair_found: PairFoundFn,
+    pair_lost: unsafe extern "C" fn(*mut SimulationFilterCallback, u64, u32, PxFilterData, u32, PxFilterData, bool),
+    status_change: unsafe extern "C" fn(*mut SimulationFilterCallback, *mut u64, *mut PxPairFlags, *mut PxFilterFlags) -> bool,
+    #[cfg(not(target_env = "msvc"))]
+    complete_destructor: unsafe extern "C" fn(*mut c_void),
+    deleting_destructor: unsafe extern "C" fn(*mut c_void),
+}
+
+// entities of a pair, stored when it's found, so they're available in pair_lost
+// even if the shapes are already removed
+#[derive(Clone, Copy)]
+struct PairEntities {
+    actors: [Entity; 2],
+    shapes: [Entity; 2],
+}
+
+/// `PxSimulationFilterCallback` of a scene, calling its [SimulationFilter].
+#[repr(C)]
+pub(crate) struct SimulationFilterCallback {
+    vtable: *const SimulationFilterCallbackVtable,
+    filter: Option<Arc<dyn SimulationFilter>>,
+    pairs: Mutex<HashMap<u64, PairEntities>>,
+    excluded_pairs: RwLock<ExcludedPairs>,
+}
+
+// SAFETY: filter is Send + Sync, vtable is static
+unsafe impl Send for SimulationFilterCallback {}
+unsafe impl Sync for SimulationFilterCallback {}
+
+impl SimulationFilterCallback {
+    const VTABLE: SimulationFilterCallbackVtable = SimulationFilterCallbackVtable {
+        pair_found: Self::pair_found,
+        pair_lost: Self::pair_lost,
+        status_change: Self::status_change,
+        #[cfg(not(target_env = "msvc"))]
+        complete_destructor: noop_destructor,
+        deleting_destructor: noop_destructor,
+    };
+
+    /// Must be boxed, so the pointer passed to physx stays valid.
+    pub(crate) fn new(filter: Option<Arc<dyn SimulationFilter>>) -> Box<Self> {
+        if filter.is_some() {
+            SIMULATION_FILTERS.fetch_add(1, Ordering::Relaxed);
+        }
+        Box::new(Self { vtable: &Self::VTABLE, filter, pairs: default(), excluded_pairs: default() })
+    }
+
+    pub(crate) fn set_excluded_pairs(&self, pairs: ExcludedPairs) {
+        *self.excluded_pairs.write().unwrap() = pairs;
+    }
+
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut physx_sys::PxSimulationFilterCallback {
+        self as *mut Self as *mut physx_sys::PxSimulationFilterCallback
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn on_pair_found(
+        &self,
+        pair_id: u64,
+        attributes: [u32; 2],
+        filter_data: [PxFilterData; 2],
+        actors: [*const physx_sys::PxActor; 2],
+        shapes: [*const physx_sys::PxShape; 2],
+        pair_flags: *mut PxPairFlags,
+    ) -> PxFilterFlags {
+        if self.excluded_pairs.read().unwrap().contains(filter_data[0].word3, filter_data[1].word3) {
+            return PxFilterFlags::Suppress;
+        }
+
+        let Some(filter) = self.filter.as_ref() else { return PxFilterFlags::empty(); };
+
+        // SAFETY: physx passes valid actors and shapes of this scene, all created by this crate
+        let entities = PairEntities {
+            actors: actors.map(|actor| unsafe { get_actor_entity_from_ptr(actor as *const _) }),
+            shapes: shapes.map(|shape| unsafe { get_shape_entity_from_ptr(shape) }),
+        };
+
+        let mut pair = FilterPair {
+            id: pair_id,
+            actors: entities.actors,
+            shapes: entities.shapes,
+            attributes,
+            filter_data: filter_data.map(words),
+            // SAFETY: physx passes a valid pointer for the duration of the call
+            pair_flags: unsafe { &mut *pair_flags },
+        };
+
+        let result = filter.pair_found(&mut pair);
+        if result != FilterResult::Kill {
+            self.pairs.lock().unwrap().insert(pair_id, entities);
+        }
+        result.filter_flags()
+    }
+
+    #[cfg(not(target_env = "msvc"))]
+    unsafe extern "C" fn pair_found(
+        result: *mut PxFilterFlags,
+        this: *mut SimulationFilterCallback,
+        pair_id: u64,
+        attributes0: u32, filter_data0: PxFilterData, actor0: *const physx_sys::PxActor, shape0: *const physx_sys::PxShape,
+        attributes1: u32, filter_data1: PxFilterData, actor1: *const physx_sys::PxActor, shape1: *const physx_sys::PxShape,
+        pair_flags: *mut PxPairFlags,
+    ) -> *mut PxFilterFlags {
+        // SAFETY: physx calls this with the pointer we registered in the scene,
+        // and a valid pointer for the return value
+        let this = unsafe { &*this };
+        let flags = this.on_pair_found(
+            pair_id,
+            [ attributes0, attributes1 ],
+            [ filter_data0, filter_data1 ],
+            [ actor0, actor1 ],
+            [ shape0, shape1 ],
+            pair_flags,
+        );
+        unsafe { result.write(flags) };
+        result
+    }
+
+    #[cfg(target_env = "msvc")]
+    unsafe extern "C" fn pair_found(
+        this: *mut SimulationFilterCallback,
+        result: *mut PxFilterFlags,
+        pair_id: u64,
+        attributes0: u32, filter_data0: PxFilterData, actor0: *const physx_sys::PxActor, shape0: *const physx_sys::PxShape,
+        attributes1: u32, filter_data1: PxFilterData, actor1: *const physx_sys::PxActor, shape1: *const physx_sys::PxShape,
+        pair_flags: *mut PxPairFlags,
+    ) -> *mut PxFilterFlags {
+        // SAFETY: physx calls this with the pointer we registered in the scene,
+        // and a valid pointer for the return value
+        let this = unsafe { &*this };
+        let flags = this.on_pair_found(
+            pair_id,
+            [ attributes0, attributes1 ],
+            [ filter_data0, filter_data1 ],
+            [ actor0, actor1 ],
+            [ shape0, shape1 ],
+            pair_flags,
+        );
+        unsafe { result.write(flags) };
+        result
+    }
+
+    unsafe extern "C" fn pair_lost(
+        this: *mut SimulationFilterCallback,
+        pair_id: u64,
+        _attributes0: u32, filter_data0: PxFilterData,
+        _attributes1: u32, filter_data1: PxFilterData,
+        object_removed: bool,
+    ) {
+        // SAFETY: physx calls this with the pointer we registered in the scene
+        let this = unsafe { &*this };
+        let Some(filter) = this.filter.as_ref() else { return; };
+        let Some(entities) = this.pairs.lock().unwrap().remove(&pair_id) else { return; };
+
+        filter.pair_lost(&LostPair {
+            id: pair_id,
+            actors: entities.actors,
+            shapes: entities.shapes,
+            filter_data: [ words(filter_data0), words(filter_data1) ],
+            removed: object_removed,
+        });
+    }
+
+    unsafe extern "C" fn status_change(
+        this: *mut SimulationFilterCallback,
+        pair_id: *mut u64,
+        pair_flags: *mut PxPairFlags,
+        filter_flags: *mut PxFilterFlags,
+    ) -> bool {
+        // SAFETY: physx calls this with the pointer we registered in the scene,
+        // and valid pointers for the results
+        let this = unsafe { &*this };
+        let Some(filter) = this.filter.as_ref() else { return false; };
+        let Some(change) = filter.status_change() else { return false; };
+
+        if change.result == FilterResult::Kill {
+            // killed pairs are not reported as lost
+            this.pairs.lock().unwrap().remove(&change.id);
+        }
+
+        unsafe {
+            pair_id.write(change.id);
+            pair_flags.write(change.pair_flags);
+            filter_flags.write(change.result.filter_flags());
+        }
+        true
+    }
+}
+
+impl Drop for SimulationFilterCallback {
+    fn drop(&mut self) {
+        if self.filter.is_some() {
+            SIMULATION_FILTERS.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Exclusion id of an actor, stored in simulation filter data `word3` of its shapes
+/// (0 if it has no exclusions).
+pub(crate) fn exclusion_id(word3: u32) -> u32 {
+    (word3 & EXCLUSION_ID_MASK) >> EXCLUSION_ID_SHIFT
+}
+
+/// Pairs of exclusion ids that never collide, see [CollisionExclusions](crate::plugins::collision_exclusions::CollisionExclusions).
+///
+/// Each scene has its own table, checked by its filter callback (crate shader only
+/// passes pairs there if both shapes have an exclusion id).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct ExcludedPairs(BTreeSet<(u32, u32)>);
+
+impl ExcludedPairs {
+    pub fn new(pairs: impl IntoIterator<Item = (u32, u32)>) -> Self {
+        Self(pairs.into_iter().map(|(a, b)| (a.min(b), a.max(b))).collect())
+    }
+
+    /// Whether shapes with these `word3` values of simulation filter data are excluded.
+    pub fn contains(&self, word3_0: u32, word3_1: u32) -> bool {
+        let (id0, id1) = (exclusion_id(word3_0), exclusion_id(word3_1));
+        if id0 == 0 || id1 == 0 { return false; }
+        self.0.contains(&(id0.min(id1), id0.max(id1)))
+    }
+}
+
+/// Filter data of a shape, updated in [ShapeFilterSync::update].
+pub(crate) struct ShapeFilter {
+    pub shape: Entity,
+    /// Actor of the shape (same as `shape` if the shape is on the actor entity).
+    pub actor: Option<Entity>,
+    pub simulation_filter_data: [u32; 4],
+    /// Make PhysX filter pairs of the shape again, even if filter data stays the same.
+    pub reset_filtering: bool,
+}
+
+impl ShapeFilter {
+    /// Replace bits of simulation filter data `word3` selected by `mask`, keeping the other bits.
+    pub fn set_word3_bits(&mut self, mask: u32, bits: u32) {
+        let word3 = &mut self.simulation_filter_data[3];
+        *word3 = (*word3 & !mask) | (bits & mask);
+    }
+
+    /// Component set on the shape, or on its actor if the shape doesn't have one.
+    pub fn get<'a, T: Component>(&self, query: &'a Query<&T>) -> Option<&'a T> {
+        query.get(self.shape).ok().or_else(|| query.get(self.actor?).ok())
+    }
+}
+
+/// Writes crate settings into filter data of shapes, used by sync systems
+/// of plugins that rely on crate filter shader.
+#[derive(SystemParam)]
+pub(crate) struct ShapeFilterSync<'w, 's> {
+    scene: ResMut<'w, Scene>,
+    shapes: Query<'w, 's, (&'static mut ShapeHandle, Option<&'static ChildOf>, Has<bpx::RigidBody>)>,
+    children: Query<'w, 's, &'static Children>,
+}
+
+impl ShapeFilterSync<'_, '_> {
+    pub fn scene_mut(&mut self) -> &mut Scene {
+        &mut self.scene
+    }
+
+    /// Update filter data of `changed` entities, which can be shapes or actors
+    /// (settings on actors apply to their nested shapes).
+    ///
+    /// [ShapeFilterData] overwrites the whole filter data, so callers should
+    /// include entities where it changed, and run after it to re-apply their bits.
+    ///
+    /// Filter data is only written back to PhysX if changed (setting filter data
+    /// makes PhysX filter all pairs of the shape again).
+    pub fn update(
+        &mut self,
+        changed: impl IntoIterator<Item = Entity>,
+        mut update: impl FnMut(&mut ShapeFilter),
+    ) {
+        let mut dirty = changed.into_iter().collect::<EntityHashSet>();
+
+        for entity in dirty.iter().copied().collect::<Vec<_>>() {
+            if let Ok(children) = self.children.get(entity) {
+                dirty.extend(children.iter());
+            }
+        }
+
+        for entity in dirty {
+            let Ok((mut handle, parent, is_actor)) = self.shapes.get_mut(entity) else { continue; };
+
+            let mut scene = self.scene.get_mut();
+            // SAFETY: scene is locked for writing above
+            let shape = unsafe { handle.get_mut_unsafe() }.as_mut_ptr();
+
+            let simulation_filter_data = words(unsafe { PxShape_getSimulationFilterData(shape) });
+
+            let mut filter = ShapeFilter {
+                shape: entity,
+                actor: if is_actor { Some(entity) } else { parent.map(|parent| parent.parent()) },
+                simulation_filter_data,
+                reset_filtering: false,
+            };
+            update(&mut filter);
+
+            if filter.simulation_filter_data != simulation_filter_data {
+                let data = filter_data(filter.simulation_filter_data);
+                unsafe { PxShape_setSimulationFilterData_mut(shape, &data) };
+            }
+
+            if filter.reset_filtering {
+                let actor = unsafe { PxShape_getActor(shape) };
+                if !actor.is_null() {
+                    unsafe { PxScene_resetFiltering_mut_1(scene.as_mut_ptr(), actor, &shape, 1) };
+                }
+            }
+        }
+    }
+}
+
+fn words(data: PxFilterData) -> [u32; 4] {
+    [ data.word0, data.word1, data.word2, data.word3 ]
+}
+
+fn filter_data(words: [u32; 4]) -> PxFilterData {
+    let [ word0, word1, word2, word3 ] = words;
+    unsafe { PxFilterData_new_2(word0, word1, word2, word3) }
+}
+
+/// Symmetric collision layers test (see [CollisionLayers](crate::plugins::collision_layers::CollisionLayers))
+/// on filter data of two shapes, shapes without layers are in all layers.
+pub(crate) fn layers_interact(data0: [u32; 4], data1: [u32; 4]) -> bool {
+    let layers = |data: [u32; 4]| {
+        if data[3] & COLLISION_LAYERS != 0 { (data[0], data[1]) } else { (u32::MAX, u32::MAX) }
+    };
+
+    let (memberships0, filters0) = layers(data0);
+    let (memberships1, filters1) = layers(data1);
+    memberships0 & filters1 != 0 && memberships1 & filters0 != 0
+}
+
+/// Filter shader used by plugins of this crate.
+///
+/// Custom filter shaders can call this function first, and then adjust the resulting flags.
+///
+/// # Safety
+/// Must be called by PhysX (or from another filter shader) with a valid pointer.
+pub unsafe extern "C" fn simulation_filter_shader(info: *mut FilterShaderCallbackInfo) -> PxFilterFlags {
+    let info = unsafe { &mut *info };
+    let pair_flags = unsafe { &mut *info.pairFlags };
+
+    if !layers_interact(words(info.filterData0), words(info.filterData1)) {
+        return PxFilterFlags::Suppress;
+    }
+    let is_trigger = unsafe {
+        phys_PxFilterObjectIsTrigger(info.attributes0) || phys_PxFilterObjectIsTrigger(info.attributes1)
+    };
+
+    if is_trigger {
+        *pair_flags = PxPairFlags::TriggerDefault;
+        return callback_flags(info);
+    }
+
+    // contact reports are requested if either of the shapes wants them
+    let flags = info.filterData0.word3 | info.filterData1.word3;
+
+    *pair_flags = PxPairFlags::ContactDefault;
+
+    if flags & NOTIFY_TOUCH_FOUND != 0 {
+        *pair_flags |= PxPairFlags::NotifyTouchFound;
+    }
+    if flags & NOTIFY_TOUCH_PERSISTS != 0 {
+        *pair_flags |= PxPairFlags::NotifyTouchPersists;
+    }
+    if flags & NOTIFY_TOUCH_LOST != 0 {
+        *pair_flags |= PxPairFlags::NotifyTouchLost;
+    }
+    if flags & NOTIFY_CONTACT_POINTS != 0 {
+        *pair_flags |= PxPairFlags::NotifyContactPoints;
+    }
+    if flags & NOTIFY_FORCE_THRESHOLD != 0 {
+        // contact points are needed to compute contact forces
+        *pair_flags |= PxPairFlags::NotifyContactPoints
+            | PxPairFlags::NotifyThresholdForceFound
+            | PxPairFlags::NotifyThresholdForcePersists
+            | PxPairFlags::NotifyThresholdForceLost;
+    }
+    if flags & MODIFY_CONTACTS != 0 {
+        *pair_flags |= PxPairFlags::ModifyContacts;
+    }
+
+    callback_flags(info)
+}
+
+// pairs are passed to filter callback of their scene, which checks excluded pairs
+// and calls SimulationFilter
+fn callback_flags(info: &FilterShaderCallbackInfo) -> PxFilterFlags {
+    let has_exclusions = exclusion_id(info.filterData0.word3) != 0 && exclusion_id(info.filterData1.word3) != 0;
+
+    if has_exclusions || SIMULATION_FILTERS.load(Ordering::Relaxed) > 0 {
+        PxFilterFlags::Callback
+    } else {
+        PxFilterFlags::empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word3_bits_dont_overlap() {
+        let bits = [
+            NOTIFY_TOUCH_FOUND,
+            NOTIFY_TOUCH_PERSISTS,
+            NOTIFY_TOUCH_LOST,
+            NOTIFY_CONTACT_POINTS,
+            NOTIFY_FORCE_THRESHOLD,
+            MODIFY_CONTACTS,
+            COLLISION_LAYERS,
+            EXCLUSION_ID_MASK,
+        ];
+
+        for (index, a) in bits.iter().enumerate() {
+            for b in bits[index + 1..].iter() {
+                assert_eq!(a & b, 0, "{a:#x} overlaps {b:#x}");
+            }
+        }
+
+        assert_eq!(
+            CONTACT_EVENTS_MASK,
+            NOTIFY_TOUCH_FOUND | NOTIFY_TOUCH_PERSISTS | NOTIFY_TOUCH_LOST | NOTIFY_CONTACT_POINTS | NOTIFY_FORCE_THRESHOLD,
+        );
+    }
+
+    #[test]
+    fn set_word3_bits_keeps_other_bits() {
+        let mut filter = ShapeFilter {
+            shape: Entity::PLACEHOLDER,
+            actor: None,
+            simulation_filter_data: [ 1, 2, 3, MODIFY_CONTACTS | (7 << EXCLUSION_ID_SHIFT) ],
+            reset_filtering: false,
+        };
+
+        filter.set_word3_bits(CONTACT_EVENTS_MASK, NOTIFY_TOUCH_FOUND | NOTIFY_TOUCH_LOST);
+        assert_eq!(
+            filter.simulation_filter_data,
+            [ 1, 2, 3, MODIFY_CONTACTS | (7 << EXCLUSION_ID_SHIFT) | NOTIFY_TOUCH_FOUND | NOTIFY_TOUCH_LOST ],
+        );
+
+        // bits outside of the mask are ignored
+        filter.set_word3_bits(EXCLUSION_ID_MASK, (9 << EXCLUSION_ID_SHIFT) | COLLISION_LAYERS);
+        let word3 = filter.simulation_filter_data[3];
+        assert_eq!(exclusion_id(word3), 9);
+        assert_eq!(word3 & CONTACT_EVENTS_MASK, NOTIFY_TOUCH_FOUND | NOTIFY_TOUCH_LOST);
+        assert_eq!(word3 & COLLISION_LAYERS, 0);
+    }
+
+    #[test]
+    fn layers_interact_both_ways() {
+        let layers = |memberships, filters| [ memberships, filters, 0, COLLISION_LAYERS ];
+
+        assert!(layers_interact(layers(0b01, 0b10), layers(0b10, 0b01)));
+        assert!(!layers_interact(layers(0b01, 0b10), layers(0b10, 0b10)));
+        assert!(!layers_interact(layers(0b10, 0b10), layers(0b01, 0b10)));
+
+        // shapes without layers are in all layers, their words 0 and 1 are ignored
+        assert!(layers_interact(layers(0b01, 0b01), [ 0, 0, 0, 0 ]));
+        assert!(!layers_interact(layers(0b01, 0), [ 0, 0, 0, 0 ]));
+    }
+
+    #[test]
+    fn excluded_pairs_match_ids_in_any_order() {
+        let word3 = |id: u32| (id << EXCLUSION_ID_SHIFT) | NOTIFY_TOUCH_FOUND;
+        let pairs = ExcludedPairs::new([ (3, 1) ]);
+
+        assert!(pairs.contains(word3(1), word3(3)));
+        assert!(pairs.contains(word3(3), word3(1)));
+        assert!(!pairs.contains(word3(1), word3(2)));
+        // shapes of actors without exclusions have id 0
+        assert!(!pairs.contains(0, word3(3)));
+    }
+
+    #[derive(Default)]
+    struct Pairs {
+        found: Mutex<Vec<([Entity; 2], [Entity; 2])>>,
+        lost: Mutex<Vec<([Entity; 2], [Entity; 2], bool)>>,
+    }
+
+    struct RecordPairs(Arc<Pairs>);
+
+    impl SimulationFilter for RecordPairs {
+        fn pair_found(&self, pair: &mut FilterPair) -> FilterResult {
+            self.0.found.lock().unwrap().push((pair.actors, pair.shapes));
+            FilterResult::Keep
+        }
+
+        fn pair_lost(&self, pair: &LostPair) {
+            self.0.lost.lock().unwrap().push((pair.actors, pair.shapes, pair.removed));
+        }
+    }
+
+    fn sorted(mut entities: [Entity; 2]) -> [Entity; 2] {
+        entities.sort();
+        entities
+    }
+
+    // runs a real scene, so physx calls the callback through its vtable
+    #[test]
+    fn simulation_filter_callback() {
+        let _guard = crate::core::foundation::test_lock();
+        let pairs = Arc::new(Pairs::default());
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            PhysicsCore::new()
+                .with_timestep(TimestepMode::Custom)
+                .with_simulation_filter(RecordPairs(pairs.clone())),
+        ));
+        app.finish();
+        app.cleanup();
+
+        let step = |app: &mut App| {
+            let world = app.world_mut();
+            world.resource_mut::<PhysicsTime>().advance_by(std::time::Duration::from_secs_f32(1. / 60.));
+            world.run_schedule(PhysicsSchedule);
+        };
+
+        let geometry = app.world_mut().resource_mut::<Assets<bpx::Geometry>>().add(Sphere::new(1.));
+        let [ a, b ] = [ (RigidBody::Static, Vec3::ZERO), (RigidBody::Dynamic, Vec3::new(1.5, 0., 0.)) ]
+            .map(|(body, position)| {
+                app.world_mut().spawn((
+                    body,
+                    Shape { geometry: geometry.clone(), ..default() },
+                    Transform::from_translation(position),
+                )).id()
+            });
+
+        step(&mut app);
+        step(&mut app);
+        let found = pairs.found.lock().unwrap().clone();
+        assert_eq!(found.len(), 1);
+        // shapes are attached to actor entities here
+        assert_eq!(sorted(found[0].0), sorted([ a, b ]));
+        assert_eq!(sorted(found[0].1), sorted([ a, b ]));
+        assert!(pairs.lost.lock().unwrap().is_empty());
+
+        app.world_mut().despawn(b);
+        step(&mut app);
+        step(&mut app);
+        let lost = pairs.lost.lock().unwrap().clone();
+        assert_eq!(lost.len(), 1);
+        assert_eq!(sorted(lost[0].0), sorted([ a, b ]));
+        assert_eq!(sorted(lost[0].1), sorted([ a, b ]));
+        assert!(lost[0].2);
+    }
+}
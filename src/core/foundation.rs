@@ -43,6 +43,31 @@ impl Physics {
     }
 }
 
+/// Lock held by unit tests while they use PhysX.
+///
+/// PhysX foundation can only exist once per process, so tests take turns using it.
+#[cfg(test)]
+pub(crate) fn test_lock() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    LOCK.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Physics for unit tests, see [test_lock].
+#[cfg(test)]
+pub(crate) fn test_physics() -> TestPhysics {
+    let guard = test_lock();
+    TestPhysics { physics: Physics::new(&default()), _guard: guard }
+}
+
+#[cfg(test)]
+#[derive(Deref, DerefMut)]
+pub(crate) struct TestPhysics {
+    #[deref]
+    physics: Physics,
+    // must be dropped after physics
+    _guard: std::sync::MutexGuard<'static, ()>,
+}
+
 #[derive(Clone)]
 /// Descriptor class for creating a physics foundation.
 pub struct FoundationDescriptor {
@@ -393,6 +393,107 @@ impl Geometry {
         };
         self
     }
+
+    /// Whether this is a convex geometry (sphere, capsule, box or convex mesh).
+    pub fn is_convex(&self) -> bool {
+        matches!(
+            self.obj,
+            GeometryInner::Sphere(_)
+                | GeometryInner::Capsule(_)
+                | GeometryInner::Box(_)
+                | GeometryInner::ConvexMesh { .. }
+        )
+    }
+
+    /// Call `f` with a raw PhysX geometry and its pose, when placed at `transform`.
+    ///
+    /// Mesh geometries are constructed on demand, so the pointer is only
+    /// valid for the duration of the call. Plane normal is applied to the pose.
+    pub fn with_raw<R>(
+        &self,
+        transform: &Transform,
+        f: impl FnOnce(*const physx_sys::PxGeometry, &physx_sys::PxTransform) -> R,
+    ) -> R {
+        let raw = self.to_raw(transform);
+        f(raw.as_ptr(), raw.pose())
+    }
+
+    /// Raw PhysX geometry and its pose, when placed at `transform`.
+    ///
+    /// Unlike [with_raw](Self::with_raw), it can be used together with other
+    /// raw geometries (including ones sharing the same mesh).
+    pub fn to_raw(&self, transform: &Transform) -> RawGeometry<'_> {
+        let mut pose = transform.to_physx_sys();
+
+        // mesh is only locked while geometry is constructed, physx doesn't modify
+        // meshes in queries, and mesh is kept alive by `self`
+        let inner = match &self.obj {
+            GeometryInner::Sphere(geom) => RawGeometryInner::Sphere(geom),
+            GeometryInner::Plane { plane, normal } => {
+                // same as in ShapeHandle::create_shape, physx plane always faces X
+                pose = (*transform * Transform::from_rotation(Quat::from_rotation_arc(Vec3::X, **normal)))
+                    .to_physx_sys();
+                RawGeometryInner::Plane(plane)
+            }
+            GeometryInner::Capsule(geom) => RawGeometryInner::Capsule(geom),
+            GeometryInner::Box(geom) => RawGeometryInner::Box(geom),
+            GeometryInner::ConvexMesh { mesh, scale, flags } => {
+                let mut mesh = mesh.lock().unwrap();
+                RawGeometryInner::ConvexMesh(PxConvexMeshGeometry::new(mesh.as_mut(), scale, *flags))
+            }
+            GeometryInner::TriangleMesh { mesh, scale, flags } => {
+                let mut mesh = mesh.lock().unwrap();
+                RawGeometryInner::TriangleMesh(PxTriangleMeshGeometry::new(mesh.as_mut(), scale, *flags))
+            }
+            GeometryInner::HeightField { mesh, scale, flags } => {
+                let mut mesh = mesh.lock().unwrap();
+                RawGeometryInner::HeightField(PxHeightFieldGeometry::new(
+                    mesh.as_mut(),
+                    *flags,
+                    scale.scale.y,
+                    scale.scale.x,
+                    scale.scale.z,
+                ))
+            }
+        };
+
+        RawGeometry { inner, pose }
+    }
+}
+
+/// Raw PhysX geometry with its pose, see [Geometry::to_raw].
+pub struct RawGeometry<'a> {
+    inner: RawGeometryInner<'a>,
+    pose: physx_sys::PxTransform,
+}
+
+enum RawGeometryInner<'a> {
+    Sphere(&'a PxSphereGeometry),
+    Capsule(&'a PxCapsuleGeometry),
+    Box(&'a PxBoxGeometry),
+    Plane(&'a PxPlaneGeometry),
+    // these reference meshes owned by the original geometry, hence the lifetime
+    ConvexMesh(PxConvexMeshGeometry),
+    TriangleMesh(PxTriangleMeshGeometry),
+    HeightField(PxHeightFieldGeometry),
+}
+
+impl RawGeometry<'_> {
+    pub fn as_ptr(&self) -> *const physx_sys::PxGeometry {
+        match &self.inner {
+            RawGeometryInner::Sphere(geom) => geom.as_ptr(),
+            RawGeometryInner::Capsule(geom) => geom.as_ptr(),
+            RawGeometryInner::Box(geom) => geom.as_ptr(),
+            RawGeometryInner::Plane(geom) => geom.as_ptr(),
+            RawGeometryInner::ConvexMesh(geom) => geom.as_ptr(),
+            RawGeometryInner::TriangleMesh(geom) => geom.as_ptr(),
+            RawGeometryInner::HeightField(geom) => geom.as_ptr(),
+        }
+    }
+
+    pub fn pose(&self) -> &physx_sys::PxTransform {
+        &self.pose
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -1,5 +1,7 @@
 //! Basic physics parts belonging to [PhysicsCore](crate::PhysicsCore) plugin.
 pub mod articulation;
+pub mod contact_modify;
+pub mod filter;
 pub mod foundation;
 pub mod geometry;
 pub mod material;
@@ -1,5 +1,7 @@
 //! A scene is a collection of bodies and constraints which can interact.
+use std::ffi::c_void;
 use std::ptr::null;
+use std::sync::Arc;
 
 use bevy::prelude::*;
 use physx::prelude::*;
@@ -11,87 +13,320 @@ use physx::scene::{
 };
 use physx::traits::Class;
 use physx_sys::{
+    create_simulation_event_callbacks,
+    destroy_simulation_event_callback,
+    enable_custom_filter_shader,
+    get_default_simulation_filter_shader,
+    phys_PxDefaultCpuDispatcherCreate,
+    PxDefaultCpuDispatcherWaitForWorkMode,
+    PxDefaultCpuDispatcher_release_mut,
+    PxPhysics_createScene_mut,
+    PxPhysics_getTolerancesScale,
+    PxSceneDesc_new,
+    PxScene_flushQueryUpdates_mut,
     PxScene_lockRead_mut,
     PxScene_lockWrite_mut,
     PxScene_removeArticulation_mut,
+    PxScene_setSimulationEventCallback_mut,
     PxScene_unlockRead_mut,
     PxScene_unlockWrite_mut,
+    SimulationEventCallbackInfo,
 };
 
+use crate::core::contact_modify::{ContactModifier, ContactModifyCallback};
+use crate::core::filter::{ExcludedPairs, SimulationFilter, SimulationFilterCallback};
 use crate::prelude::{self as bpx, *};
 use crate::types::*;
 
+type CollisionFn = dyn FnMut(&physx_sys::PxContactPairHeader, &[physx_sys::PxContactPair]) + Send + Sync;
+type TriggerFn = dyn FnMut(&[physx_sys::PxTriggerPair]) + Send + Sync;
+type ConstraintBreakFn = dyn FnMut(&[physx_sys::PxConstraintInfo]) + Send + Sync;
+
+#[derive(Resource, Default)]
+/// Callbacks installed by plugins of this crate, called after the ones in [SceneDescriptor].
+///
+/// Plugins add their callbacks to this resource in `build()`,
+/// and it's consumed when the scene is created.
+pub struct BuiltinCallbacks {
+    pub(crate) on_collision: Vec<Box<CollisionFn>>,
+    pub(crate) on_trigger: Vec<Box<TriggerFn>>,
+    pub(crate) on_constraint_break: Vec<Box<ConstraintBreakFn>>,
+    pub(crate) on_wake_sleep: Option<OnWakeSleep>,
+    pub(crate) contact_modifiers: Vec<Arc<dyn ContactModifier>>,
+    /// Plugins that rely on crate filter shader set this flag.
+    pub(crate) filter_shader: bool,
+}
+
 #[derive(Resource)]
 /// A scene is a collection of bodies and constraints which can interact.
 pub struct Scene {
     scene: SceneRwLock<Owner<PxScene>>,
     use_physx_lock: bool,
     pub(crate) send_sleep_notifies: bool,
+    // must be dropped after the scene
+    callbacks: SceneCallbacks,
 }
 
-impl Scene {
-    pub fn new(physics: &mut bpx::Physics, d: &SceneDescriptor, on_wake_sleep: Option<OnWakeSleep>) -> Self {
-        use physx::physics::Physics; // physx trait clashes with our wrapper
+// objects referenced by PxScene, owned by the scene wrapper
+struct SceneCallbacks {
+    simulation_event_callback: *mut physx_sys::PxSimulationEventCallback,
+    cpu_dispatcher: *mut physx_sys::PxDefaultCpuDispatcher,
+    _event_callbacks: Box<SimulationEventCallbacks>,
+    filter_callback: Box<SimulationFilterCallback>,
+    _contact_modify_callback: Option<Box<ContactModifyCallback>>,
+}
+
+// SAFETY: pointers are only used on drop
+unsafe impl Send for SceneCallbacks {}
+unsafe impl Sync for SceneCallbacks {}
+
+impl Drop for SceneCallbacks {
+    fn drop(&mut self) {
+        unsafe {
+            destroy_simulation_event_callback(self.simulation_event_callback);
+            PxDefaultCpuDispatcher_release_mut(self.cpu_dispatcher);
+        }
+    }
+}
+
+// callbacks of the scene, which PxSimulationEventCallback created by physx-sys calls
+// with pointers to these fields as user data
+struct SimulationEventCallbacks {
+    on_collision: Option<OnCollision>,
+    on_trigger: Option<OnTrigger>,
+    on_constraint_break: Option<OnConstraintBreak>,
+    on_wake_sleep: Option<OnWakeSleep>,
+    on_advance: Option<OnAdvance>,
+}
 
-        // PxBounds3 doesn't have Clone/Copy, even though it should
-        let sanity_bounds = unsafe {
-            std::mem::transmute_copy(&d.sanity_bounds)
-            //physx::math::PxBounds3::from(*(std::mem::transmute::<&physx::math::PxBounds3, &physx_sys::PxBounds3>(&d.sanity_bounds)))
+impl SimulationEventCallbacks {
+    /// # Safety
+    /// Returned callback must be destroyed before `self`.
+    unsafe fn create_px_callback(&mut self) -> *mut physx_sys::PxSimulationEventCallback {
+        fn user_data<T>(callback: &mut Option<T>) -> *mut c_void {
+            callback.as_mut().map_or(std::ptr::null_mut(), |callback| callback as *mut T as *mut c_void)
+        }
+
+        let info = SimulationEventCallbackInfo {
+            collision_callback: self.on_collision.is_some().then_some(Self::on_collision as _),
+            collision_user_data: user_data(&mut self.on_collision),
+            trigger_callback: self.on_trigger.is_some().then_some(Self::on_trigger as _),
+            trigger_user_data: user_data(&mut self.on_trigger),
+            constraint_break_callback: self.on_constraint_break.is_some().then_some(Self::on_constraint_break as _),
+            constraint_break_user_data: user_data(&mut self.on_constraint_break),
+            wake_sleep_callback: self.on_wake_sleep.is_some().then_some(Self::on_wake_sleep as _),
+            wake_sleep_user_data: user_data(&mut self.on_wake_sleep),
+            advance_callback: self.on_advance.is_some().then_some(Self::on_advance as _),
+            advance_user_data: user_data(&mut self.on_advance),
         };
 
+        unsafe { create_simulation_event_callbacks(&info) }
+    }
+
+    unsafe fn slice<'a, T, U>(ptr: *const U, count: u32) -> &'a [T] {
+        if ptr.is_null() || count == 0 { return &[]; }
+        unsafe { std::slice::from_raw_parts(ptr as *const T, count as usize) }
+    }
+
+    unsafe extern "C" fn on_collision(
+        user_data: *mut c_void,
+        header: *const physx_sys::PxContactPairHeader,
+        pairs: *const physx_sys::PxContactPair,
+        count: u32,
+    ) {
+        let callback = unsafe { &mut *(user_data as *mut OnCollision) };
+        callback.on_collision(unsafe { &*header }, unsafe { Self::slice(pairs, count) });
+    }
+
+    unsafe extern "C" fn on_trigger(user_data: *mut c_void, pairs: *const physx_sys::PxTriggerPair, count: u32) {
+        let callback = unsafe { &mut *(user_data as *mut OnTrigger) };
+        callback.on_trigger(unsafe { Self::slice(pairs, count) });
+    }
+
+    unsafe extern "C" fn on_constraint_break(user_data: *mut c_void, constraints: *const physx_sys::PxConstraintInfo, count: u32) {
+        let callback = unsafe { &mut *(user_data as *mut OnConstraintBreak) };
+        callback.on_constraint_break(unsafe { Self::slice(constraints, count) });
+    }
+
+    unsafe extern "C" fn on_wake_sleep(user_data: *mut c_void, actors: *const *const physx_sys::PxActor, count: u32, is_waking: bool) {
+        let callback = unsafe { &mut *(user_data as *mut OnWakeSleep) };
+        // ActorMap is a transparent wrapper over PxActor, same as in physx crate callbacks
+        callback.on_wake_sleep(unsafe { Self::slice(actors, count) }, is_waking);
+    }
+
+    unsafe extern "C" fn on_advance(
+        user_data: *mut c_void,
+        bodies: *const *const physx_sys::PxRigidBody,
+        transforms: *const physx_sys::PxTransform,
+        count: u32,
+    ) {
+        let callback = unsafe { &*(user_data as *const OnAdvance) };
+        callback.on_advance(unsafe { Self::slice(bodies, count) }, unsafe { Self::slice(transforms, count) });
+    }
+}
+
+impl Scene {
+    pub fn new(physics: &mut bpx::Physics, d: &SceneDescriptor, builtin: BuiltinCallbacks) -> Self {
+        // contact modification is requested through crate filter shader
+        let use_filter_shader = builtin.filter_shader
+            || d.simulation_filter.is_some()
+            || d.contact_modifier.is_some()
+            || !builtin.contact_modifiers.is_empty();
+
         // not needless match, as it doesn't support Clone/Copy
         #[allow(clippy::needless_match)]
         let simulation_filter_shader = match d.simulation_filter_shader {
+            FilterShaderDescriptor::Default if use_filter_shader => {
+                FilterShaderDescriptor::Custom(crate::core::filter::simulation_filter_shader)
+            }
             FilterShaderDescriptor::Default => FilterShaderDescriptor::Default,
             FilterShaderDescriptor::Custom(f) => FilterShaderDescriptor::Custom(f),
             FilterShaderDescriptor::CallDefaultFirst(f) => FilterShaderDescriptor::CallDefaultFirst(f),
         };
 
+        if use_filter_shader && !matches!(d.simulation_filter_shader, FilterShaderDescriptor::Default) {
+            bevy::log::warn!("custom simulation filter shader is used, built-in collision events and filtering will not work unless it calls bevy_mod_physx::core::filter::simulation_filter_shader");
+        }
+
+        let BuiltinCallbacks {
+            on_collision: mut builtin_on_collision,
+            on_trigger: mut builtin_on_trigger,
+            on_constraint_break: mut builtin_on_constraint_break,
+            on_wake_sleep,
+            contact_modifiers: builtin_contact_modifiers,
+            ..
+        } = builtin;
+
         let send_sleep_notifies = on_wake_sleep.is_some();
 
-        let scene = physics
-            .create(physx::traits::descriptor::SceneDescriptor {
-                on_collide: d.on_collision.as_ref().map(|x| x.initialize()),
-                on_trigger: d.on_trigger.as_ref().map(|x| x.initialize()),
-                on_constraint_break: d.on_constraint_break.as_ref().map(|x| x.initialize()),
-                on_wake_sleep: on_wake_sleep.as_ref().map(|x| x.initialize()),
-                on_advance: d.on_advance.as_ref().map(|x| x.initialize()),
-                gravity: d.gravity.to_physx(),
-                kine_kine_filtering_mode: d.kine_kine_filtering_mode,
-                static_kine_filtering_mode: d.static_kine_filtering_mode,
-                broad_phase_type: d.broad_phase_type,
-                limits: d.limits,
-                friction_type: d.friction_type,
-                solver_type: d.solver_type,
-                bounce_threshold_velocity: d.bounce_threshold_velocity,
-                friction_offset_threshold: d.friction_offset_threshold,
-                ccd_max_separation: d.ccd_max_separation,
-                flags: d.flags,
-                static_structure: d.static_structure,
-                dynamic_structure: d.dynamic_structure,
-                dynamic_tree_rebuild_rate_hint: d.dynamic_tree_rebuild_rate_hint,
-                scene_query_update_mode: d.scene_query_update_mode,
-                solver_batch_size: d.solver_batch_size,
-                solver_articulation_batch_size: d.solver_articulation_batch_size,
-                nb_contact_data_blocks: d.nb_contact_data_blocks,
-                max_nb_contact_data_blocks: d.max_nb_contact_data_blocks,
-                max_bias_coefficient: d.max_bias_coefficient,
-                contact_report_stream_buffer_size: d.contact_report_stream_buffer_size,
-                ccd_max_passes: d.ccd_max_passes,
-                ccd_threshold: d.ccd_threshold,
-                wake_counter_reset_value: d.wake_counter_reset_value,
-                sanity_bounds,
-                simulation_filter_shader,
-                thread_count: d.thread_count,
-                gpu_max_num_partitions: d.gpu_max_num_partitions,
-                ..physx::traits::descriptor::SceneDescriptor::new(())
-            })
-            .unwrap();
+        // user callbacks are called first, then all built-in ones
+        let on_collide = match d.on_collision.as_ref().map(|x| x.initialize()) {
+            user if builtin_on_collision.is_empty() => user,
+            mut user => Some(OnCollision::new(move |header, pairs| {
+                if let Some(user) = user.as_mut() { user.on_collision(header, pairs); }
+                for callback in builtin_on_collision.iter_mut() { callback(header, pairs); }
+            })),
+        };
+
+        let on_trigger = match d.on_trigger.as_ref().map(|x| x.initialize()) {
+            user if builtin_on_trigger.is_empty() => user,
+            mut user => Some(OnTrigger::new(move |pairs| {
+                if let Some(user) = user.as_mut() { user.on_trigger(pairs); }
+                for callback in builtin_on_trigger.iter_mut() { callback(pairs); }
+            })),
+        };
+
+        let on_constraint_break = match d.on_constraint_break.as_ref().map(|x| x.initialize()) {
+            user if builtin_on_constraint_break.is_empty() => user,
+            mut user => Some(OnConstraintBreak::new(move |constraints| {
+                if let Some(user) = user.as_mut() { user.on_constraint_break(constraints); }
+                for callback in builtin_on_constraint_break.iter_mut() { callback(constraints); }
+            })),
+        };
+
+        let on_wake_sleep = on_wake_sleep.as_ref().map(|x| x.initialize());
+        let on_advance = d.on_advance.as_ref().map(|x| x.initialize());
+        let mut event_callbacks = Box::new(SimulationEventCallbacks {
+            on_collision: on_collide,
+            on_trigger,
+            on_constraint_break,
+            on_wake_sleep,
+            on_advance,
+        });
+
+        // user modifier is called first, then all built-in ones
+        let contact_modifiers = d.contact_modifier.iter().cloned()
+            .chain(builtin_contact_modifiers)
+            .collect::<Vec<_>>();
+
+        let mut contact_modify_callback = if contact_modifiers.is_empty() {
+            None
+        } else {
+            Some(ContactModifyCallback::new(contact_modifiers))
+        };
+
+        let mut filter_callback = SimulationFilterCallback::new(d.simulation_filter.clone());
+
+        // scene is created through physx-sys instead of physx crate descriptor,
+        // because filter callback can only be set in PxSceneDesc
+        let (scene, simulation_event_callback, cpu_dispatcher) = unsafe {
+            let physics = physics.physics_mut().as_mut_ptr();
+            let mut desc = PxSceneDesc_new(PxPhysics_getTolerancesScale(physics));
+
+            desc.gravity = d.gravity.to_physx_sys();
+            desc.kineKineFilteringMode = d.kine_kine_filtering_mode.into();
+            desc.staticKineFilteringMode = d.static_kine_filtering_mode.into();
+            desc.broadPhaseType = d.broad_phase_type.into();
+            desc.limits = d.limits.into();
+            desc.frictionType = d.friction_type.into();
+            desc.solverType = d.solver_type.into();
+            desc.bounceThresholdVelocity = d.bounce_threshold_velocity;
+            desc.frictionOffsetThreshold = d.friction_offset_threshold;
+            desc.ccdMaxSeparation = d.ccd_max_separation;
+            desc.flags = d.flags.into();
+            desc.staticStructure = d.static_structure.into();
+            desc.dynamicStructure = d.dynamic_structure.into();
+            desc.dynamicTreeRebuildRateHint = d.dynamic_tree_rebuild_rate_hint;
+            desc.sceneQueryUpdateMode = d.scene_query_update_mode.into();
+            desc.solverBatchSize = d.solver_batch_size;
+            desc.solverArticulationBatchSize = d.solver_articulation_batch_size;
+            desc.nbContactDataBlocks = d.nb_contact_data_blocks;
+            desc.maxNbContactDataBlocks = d.max_nb_contact_data_blocks;
+            desc.maxBiasCoefficient = d.max_bias_coefficient;
+            desc.contactReportStreamBufferSize = d.contact_report_stream_buffer_size;
+            desc.ccdMaxPasses = d.ccd_max_passes;
+            desc.ccdThreshold = d.ccd_threshold;
+            desc.wakeCounterResetValue = d.wake_counter_reset_value;
+            // PxBounds3 doesn't have Clone/Copy, even though it should
+            desc.sanityBounds = std::mem::transmute_copy::<_, physx_sys::PxBounds3>(&d.sanity_bounds);
+            desc.gpuMaxNumPartitions = d.gpu_max_num_partitions;
+
+            match simulation_filter_shader {
+                FilterShaderDescriptor::Default => {
+                    desc.filterShader = get_default_simulation_filter_shader();
+                }
+                FilterShaderDescriptor::Custom(shader) => {
+                    enable_custom_filter_shader(&mut desc as *mut _, shader, 0);
+                }
+                FilterShaderDescriptor::CallDefaultFirst(shader) => {
+                    enable_custom_filter_shader(&mut desc as *mut _, shader, 1);
+                }
+            }
+
+            desc.filterCallback = filter_callback.as_mut_ptr();
+
+            if let Some(callback) = contact_modify_callback.as_mut() {
+                desc.contactModifyCallback = callback.as_mut_ptr();
+            }
+
+            let simulation_event_callback = event_callbacks.create_px_callback();
+            desc.simulationEventCallback = simulation_event_callback;
+
+            let cpu_dispatcher = phys_PxDefaultCpuDispatcherCreate(
+                d.thread_count,
+                std::ptr::null_mut(),
+                PxDefaultCpuDispatcherWaitForWorkMode::WaitForWork,
+                0,
+            );
+            desc.cpuDispatcher = cpu_dispatcher as *mut _;
+
+            let scene = <PxScene as physx::scene::Scene>::from_raw(PxPhysics_createScene_mut(physics, &desc), ())
+                .expect("creating PhysX scene failed");
+
+            (scene, simulation_event_callback, cpu_dispatcher)
+        };
 
         Self {
             scene: SceneRwLock::new(scene),
             use_physx_lock: d.flags.contains(SceneFlags::RequireRwLock),
             send_sleep_notifies,
+            callbacks: SceneCallbacks {
+                simulation_event_callback,
+                cpu_dispatcher,
+                _event_callbacks: event_callbacks,
+                filter_callback,
+                _contact_modify_callback: contact_modify_callback,
+            },
         }
     }
 
@@ -104,11 +339,30 @@ impl Scene {
         let scene = if self.use_physx_lock { Some(self.scene.0.as_mut_ptr()) } else { None };
         SceneRwLockWriteGuard::new(&mut self.scene.0, scene)
     }
+
+    /// Set pairs of actor exclusion ids that never collide, see [crate::plugins::collision_exclusions].
+    pub(crate) fn set_excluded_pairs(&mut self, pairs: ExcludedPairs) {
+        self.callbacks.filter_callback.set_excluded_pairs(pairs);
+    }
+
+    /// Flush scene query changes, so that actors added or moved since the last
+    /// simulation step are visible to scene queries.
+    ///
+    /// PhysX does this lazily on first query otherwise, but that requires
+    /// write access, which scene queries don't have.
+    pub fn flush_query_updates(&mut self) {
+        let mut scene = self.get_mut();
+        unsafe { PxScene_flushQueryUpdates_mut(scene.as_mut_ptr()) };
+    }
 }
 
 impl Drop for Scene {
     fn drop(&mut self) {
         use physx::prelude::Scene;
+
+        // callback is owned by SceneCallbacks, so physx crate doesn't destroy it with the scene
+        unsafe { PxScene_setSimulationEventCallback_mut(self.scene.get_mut_unsafe().as_mut_ptr(), std::ptr::null_mut()) };
+
         let scene_ptr = unsafe { self.scene.get_mut_unsafe().as_mut_ptr() };
         let articulations = unsafe { self.scene.get_mut_unsafe() }.get_articulations();
 
@@ -231,6 +485,14 @@ pub struct SceneDescriptor {
     /// This is called when a breakable constraint breaks.
     pub on_constraint_break: Option<OnConstraintBreak>,
 
+    /// Custom collision filtering rules, installed as filter callback of the scene
+    /// and called for every new pair of shapes, see [crate::core::filter].
+    pub simulation_filter: Option<Arc<dyn SimulationFilter>>,
+
+    /// This is called for pairs of shapes that request contact modification,
+    /// see [crate::core::contact_modify].
+    pub contact_modifier: Option<Arc<dyn ContactModifier>>,
+
     //pub on_wake_sleep: Option<callbacks::OnWakeSleep>, // built-in callback
 
     /// Provides early access to the new pose of moving rigid bodies.
@@ -471,6 +733,8 @@ impl Default for SceneDescriptor {
             on_collision: None,
             on_trigger: None,
             on_constraint_break: None,
+            simulation_filter: None,
+            contact_modifier: None,
             on_advance: None,
             //on_wake_sleep: None, // built-in callback
             // override default gravity, as we know bevy's coordinate system,
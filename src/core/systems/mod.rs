@@ -16,3 +16,9 @@ pub fn scene_simulate(
     scene.simulate(time.delta_secs(), None, None);
     scene.fetch_results(true).unwrap();
 }
+
+pub fn scene_flush_query_updates(
+    mut scene: ResMut<crate::prelude::Scene>,
+) {
+    scene.flush_query_updates();
+}
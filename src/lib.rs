@@ -42,6 +42,9 @@ pub mod utils;
 pub use physx;
 pub use physx_sys;
 
+// derive macros refer to this crate by name, including in its own tests
+extern crate self as bevy_mod_physx;
+
 use crate::prelude as bpx;
 use crate::core::systems;
 use crate::core::material::{DefaultMaterial, DefaultMaterialHandle};
@@ -98,6 +101,10 @@ pub enum PhysicsSet {
     CreateFlush,
     /// Last label in [PhysicsSet]. Use it to order your own systems
     /// within [PhysicsSchedule].
+    ///
+    /// Scene query structures are flushed before this set (unless disabled with
+    /// [PhysicsCore] `flush_query_updates` setting), after both Create and Sync,
+    /// so that queries see actors created or teleported in this tick.
     Last,
 }
 
@@ -150,10 +157,13 @@ impl PluginGroup for PhysicsPlugins {
         group = group
             .add(PhysicsCore::default())
             .add(crate::plugins::articulation::ArticulationPlugin)
+            .add(crate::plugins::constraint_break::ConstraintBreakPlugin)
             .add(crate::plugins::damping::DampingPlugin)
             .add(crate::plugins::external_force::ExternalForcePlugin)
+            .add(crate::plugins::joint::JointPlugin)
             .add(crate::plugins::kinematic::KinematicPlugin)
             .add(crate::plugins::mass_properties::MassPropertiesPlugin)
+            .add(crate::plugins::modify_contacts::ModifyContactsPlugin)
             .add(crate::plugins::name::NamePlugin)
             .add(crate::plugins::shape_filter_data::ShapeFilterDataPlugin)
             .add(crate::plugins::shape_offsets::ShapeOffsetsPlugin)
@@ -174,6 +184,11 @@ pub struct PhysicsCore {
     pub timestep: TimestepMode,
     pub default_material: DefaultMaterial,
     pub sync_first: bool,
+    /// Flush scene query structures after [PhysicsSet::CreateFlush] and [PhysicsSet::Sync],
+    /// so that actors created or teleported in this tick are visible to scene queries.
+    ///
+    /// Default: true
+    pub flush_query_updates: bool,
 }
 
 impl PhysicsCore {
@@ -195,6 +210,23 @@ impl PhysicsCore {
         self.foundation.visual_debugger = true;
         self
     }
+
+    /// Set custom collision filtering rules, see [crate::core::filter].
+    pub fn with_simulation_filter(mut self, filter: impl bpx::SimulationFilter) -> Self {
+        self.scene.simulation_filter = Some(std::sync::Arc::new(filter));
+        self
+    }
+
+    /// Register a contact modifier, see [crate::core::contact_modify].
+    pub fn with_contact_modifier(mut self, modifier: impl bpx::ContactModifier) -> Self {
+        self.scene.contact_modifier = Some(std::sync::Arc::new(modifier));
+        self
+    }
+
+    pub fn with_flush_query_updates(mut self, flush_query_updates: bool) -> Self {
+        self.flush_query_updates = flush_query_updates;
+        self
+    }
 }
 
 impl Default for PhysicsCore {
@@ -209,6 +241,7 @@ impl Default for PhysicsCore {
                 restitution: 0.6,
             },
             sync_first: true,
+            flush_query_updates: true,
         }
     }
 }
@@ -264,6 +297,14 @@ impl Plugin for PhysicsCore {
             ApplyDeferred,
         ).in_set(PhysicsSet::CreateFlush));
 
+        if self.flush_query_updates {
+            // Sync may run either before or after Create (see `sync_first`),
+            // and both of them move actors
+            app.add_systems(PhysicsSchedule, (
+                systems::scene_flush_query_updates,
+            ).after(PhysicsSet::CreateFlush).after(PhysicsSet::Sync).before(PhysicsSet::Last));
+        }
+
         // add scheduler
         app.add_systems(PreUpdate, run_physics_schedule);
     }
@@ -271,8 +312,8 @@ impl Plugin for PhysicsCore {
     fn finish(&self, app: &mut App) {
         let mut physics = bpx::Physics::new(&self.foundation);
 
-        let wake_sleep_callback = app.world_mut().remove_resource::<crate::plugins::sleep::WakeSleepCallback>();
-        let scene = bpx::Scene::new(&mut physics, &self.scene, wake_sleep_callback.map(|x| x.0));
+        let builtin_callbacks = app.world_mut().remove_resource::<crate::core::scene::BuiltinCallbacks>();
+        let scene = bpx::Scene::new(&mut physics, &self.scene, builtin_callbacks.unwrap_or_default());
 
         app.insert_resource(scene);
 
@@ -0,0 +1,727 @@
+//! Collision events, without writing filter shaders and callbacks by hand.
+//!
+//! [CollisionEventsPlugin] installs crate filter shader (see [crate::core::filter])
+//! and a collision callback, and sends [CollisionStarted], [CollisionPersisted]
+//! and [CollisionEnded] events for pairs of touching shapes.
+//!
+//! Contact reporting is opt-in: add [ContactEvents] component to actors or shapes
+//! you're interested in. Events are sent for a pair if either of the shapes
+//! requests them. Component on a shape overrides component on its actor.
+//!
+//! Events are sent in [PhysicsSchedule] right after simulation, and can be read
+//! either in [PhysicsSchedule] or in [Update].
+//!
+//! If a shape or an actor is despawned while touching something, [CollisionEnded]
+//! is still sent, but it refers to entities that no longer exist.
+//!
+//! Same collisions are also triggered as [OnCollisionStart] and [OnCollisionEnd]
+//! observer events, targeting both actor and shape entities of each side.
+//!
+//! Currently touching pairs are also kept in [Contacts] resource, which is useful
+//! for systems that don't run every frame and can't rely on events.
+//!
+//! Add [ContactForceThreshold] to a dynamic body to receive [ContactForceEvent]
+//! when it is hit hard enough (e.g. for impact sounds or fall damage).
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_mod_physx::prelude::*;
+//! fn spawn_player(mut commands: Commands) {
+//!     commands.spawn((RigidBody::Dynamic, ContactEvents::default()));
+//! }
+//!
+//! fn print_collisions(mut events: EventReader<CollisionStarted>) {
+//!     for event in events.read() {
+//!         println!("{:?} started touching {:?}", event.actor0, event.actor1);
+//!     }
+//! }
+//!
+//! fn spawn_observed(mut commands: Commands) {
+//!     commands.spawn((RigidBody::Dynamic, ContactEvents::default()))
+//!         .observe(|trigger: Trigger<OnCollisionStart>| {
+//!             println!("{:?} hit {:?}", trigger.target(), trigger.other_actor);
+//!         });
+//! }
+//! ```
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use bevy::ecs::entity::{EntityHashMap, EntityHashSet};
+use bevy::prelude::*;
+use physx::traits::Class;
+use physx_sys::{
+    PxContactPair,
+    PxContactPairHeader,
+    PxPairFlags,
+    PxRigidDynamic_setContactReportThreshold_mut,
+    PxShape,
+    PxShape_getSimulationFilterData,
+};
+
+use crate::core::filter::{
+    ShapeFilterSync,
+    CONTACT_EVENTS_MASK,
+    NOTIFY_CONTACT_POINTS,
+    NOTIFY_FORCE_THRESHOLD,
+    NOTIFY_TOUCH_FOUND,
+    NOTIFY_TOUCH_LOST,
+    NOTIFY_TOUCH_PERSISTS,
+};
+use crate::core::scene::BuiltinCallbacks;
+use crate::plugins::shape_filter_data::shape_filter_data;
+use crate::prelude::{Scene, *};
+pub use crate::types::ContactPoint;
+use crate::types::{ContactPair, ContactPairHeader};
+
+#[derive(Component, Debug, PartialEq, Eq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+/// Request contact reports for an actor (all its shapes) or a single shape.
+///
+/// Default is to report when contact starts and ends, without contact points.
+pub struct ContactEvents {
+    /// Send [CollisionStarted] when shapes start touching.
+    pub touch_found: bool,
+    /// Send [CollisionPersisted] on every step while shapes are touching.
+    pub touch_persists: bool,
+    /// Send [CollisionEnded] when shapes stop touching.
+    pub touch_lost: bool,
+    /// Include contact points in [CollisionStarted] and [CollisionPersisted] events.
+    pub contact_points: bool,
+    /// Report contact forces exceeding contact report threshold of the rigid body.
+    ///
+    /// This is enabled automatically for shapes of bodies with [ContactForceThreshold].
+    pub force_threshold: bool,
+}
+
+impl Default for ContactEvents {
+    fn default() -> Self {
+        Self {
+            touch_found: true,
+            touch_persists: false,
+            touch_lost: true,
+            contact_points: false,
+            force_threshold: false,
+        }
+    }
+}
+
+impl ContactEvents {
+    /// Report everything, including contact points.
+    pub fn all() -> Self {
+        Self {
+            touch_found: true,
+            touch_persists: true,
+            touch_lost: true,
+            contact_points: true,
+            force_threshold: true,
+        }
+    }
+
+    fn to_filter_bits(self) -> u32 {
+        let mut bits = 0;
+        if self.touch_found { bits |= NOTIFY_TOUCH_FOUND; }
+        if self.touch_persists { bits |= NOTIFY_TOUCH_PERSISTS; }
+        if self.touch_lost { bits |= NOTIFY_TOUCH_LOST; }
+        if self.contact_points { bits |= NOTIFY_CONTACT_POINTS; }
+        if self.force_threshold { bits |= NOTIFY_FORCE_THRESHOLD; }
+        bits
+    }
+}
+
+#[derive(Component, Debug, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+/// Send [ContactForceEvent] when total normal force of a contact involving
+/// this dynamic body exceeds the threshold.
+///
+/// There's no default, as a useful threshold depends on mass of the body and
+/// what the events are used for.
+pub struct ContactForceThreshold(pub f32);
+
+// physx default, no reports
+const NO_CONTACT_FORCE_THRESHOLD: f32 = f32::MAX;
+
+/// Pair of touching shapes and their actors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collision {
+    pub actor0: Entity,
+    pub actor1: Entity,
+    pub shape0: Entity,
+    pub shape1: Entity,
+    /// Contact points between the shapes (always empty for [CollisionEnded]).
+    pub contacts: Vec<ContactPoint>,
+}
+
+impl Collision {
+    // (actor, shape, other actor, other shape) from the point of view of each side
+    fn sides(&self) -> [(Entity, Entity, Entity, Entity); 2] {
+        [
+            (self.actor0, self.shape0, self.actor1, self.shape1),
+            (self.actor1, self.shape1, self.actor0, self.shape0),
+        ]
+    }
+
+    fn key(&self) -> (Entity, Entity) {
+        (self.shape0.min(self.shape1), self.shape0.max(self.shape1))
+    }
+
+    fn is_between(&self, a: Entity, b: Entity) -> bool {
+        let side0 = |e| e == self.actor0 || e == self.shape0;
+        let side1 = |e| e == self.actor1 || e == self.shape1;
+        (side0(a) && side1(b)) || (side1(a) && side0(b))
+    }
+}
+
+/// Two shapes started touching.
+#[derive(Event, Debug, Clone, PartialEq, Deref)]
+pub struct CollisionStarted(pub Collision);
+
+/// Two shapes are still touching.
+#[derive(Event, Debug, Clone, PartialEq, Deref)]
+pub struct CollisionPersisted(pub Collision);
+
+/// Two shapes stopped touching (or one of them was removed).
+#[derive(Event, Debug, Clone, PartialEq, Deref)]
+pub struct CollisionEnded(pub Collision);
+
+/// Pairs of shapes that are currently touching, updated after each simulation step.
+///
+/// Only pairs with touch found and touch lost reports enabled (default
+/// [ContactEvents]) are tracked. Contact points are only present if requested
+/// in [ContactEvents], and they are only updated on every step if touch persists
+/// reports are enabled as well.
+///
+/// Most methods accept either actor or shape entities.
+#[derive(Resource, Debug, Default)]
+pub struct Contacts {
+    pairs: HashMap<(Entity, Entity), Collision>,
+    // actor or shape -> pairs it takes part in
+    index: EntityHashMap<HashSet<(Entity, Entity)>>,
+}
+
+impl Contacts {
+    /// Whether `a` and `b` touch each other.
+    pub fn is_touching(&self, a: Entity, b: Entity) -> bool {
+        self.between(a, b).next().is_some()
+    }
+
+    /// All pairs `entity` takes part in.
+    pub fn touching(&self, entity: Entity) -> impl Iterator<Item = &Collision> {
+        self.index.get(&entity).into_iter().flatten().filter_map(|key| self.pairs.get(key))
+    }
+
+    /// All pairs of shapes between `a` and `b`, with their contact manifolds.
+    pub fn between(&self, a: Entity, b: Entity) -> impl Iterator<Item = &Collision> {
+        self.touching(a).filter(move |collision| collision.is_between(a, b))
+    }
+
+    /// All touching pairs.
+    pub fn iter(&self) -> impl Iterator<Item = &Collision> {
+        self.pairs.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    fn insert(&mut self, collision: Collision) {
+        let key = collision.key();
+        for entity in [ collision.actor0, collision.actor1, collision.shape0, collision.shape1 ] {
+            self.index.entry(entity).or_default().insert(key);
+        }
+        self.pairs.insert(key, collision);
+    }
+
+    fn update(&mut self, collision: &Collision) {
+        if let Some(existing) = self.pairs.get_mut(&collision.key()) {
+            existing.contacts.clone_from(&collision.contacts);
+        }
+    }
+
+    fn remove(&mut self, key: (Entity, Entity)) {
+        let Some(collision) = self.pairs.remove(&key) else { return; };
+        for entity in [ collision.actor0, collision.actor1, collision.shape0, collision.shape1 ] {
+            let Some(keys) = self.index.get_mut(&entity) else { continue; };
+            keys.remove(&key);
+            if keys.is_empty() { self.index.remove(&entity); }
+        }
+    }
+
+    fn remove_entity(&mut self, entity: Entity) {
+        let Some(keys) = self.index.get(&entity) else { return; };
+        for key in keys.iter().copied().collect::<Vec<_>>() {
+            self.remove(key);
+        }
+    }
+}
+
+/// Observer event, triggered on actor and shape entities that started touching something.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OnCollisionStart {
+    /// Own shape that started touching (same as target if observed on a shape).
+    pub shape: Entity,
+    pub other_actor: Entity,
+    pub other_shape: Entity,
+}
+
+/// Observer event, triggered on actor and shape entities that stopped touching something.
+///
+/// Not triggered on entities that were despawned.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OnCollisionEnd {
+    /// Own shape that stopped touching (same as target if observed on a shape).
+    pub shape: Entity,
+    pub other_actor: Entity,
+    pub other_shape: Entity,
+}
+
+/// Contact force between two shapes exceeded [ContactForceThreshold] of one of the bodies.
+///
+/// Sent once when force first exceeds the threshold, not on every step.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct ContactForceEvent {
+    pub actor0: Entity,
+    pub actor1: Entity,
+    pub shape0: Entity,
+    pub shape1: Entity,
+    /// Sum of normal forces at all contact points.
+    pub total_normal_force: f32,
+    /// Position of the contact point with the largest impulse, in world space.
+    pub max_impulse_point: Vec3,
+}
+
+// callback doesn't know the simulation step, so impulses are converted
+// into forces in a system afterwards
+struct ContactImpulse {
+    actor0: Entity,
+    actor1: Entity,
+    shape0: Entity,
+    shape1: Entity,
+    total_normal_impulse: f32,
+    max_impulse_point: Vec3,
+}
+
+#[derive(Resource, Deref, DerefMut)]
+struct ContactImpulseReceiver(Mutex<Receiver<ContactImpulse>>);
+
+// pairs of touching shapes (by shape pointers), shared between the callback
+// and the system that forgets removed shapes
+type TouchingPairs = Arc<Mutex<HashMap<(usize, usize), Collision>>>;
+
+#[derive(Resource, Deref)]
+struct ReporterTouchingPairs(TouchingPairs);
+
+pub struct CollisionEventsPlugin;
+
+impl Plugin for CollisionEventsPlugin {
+    fn build(&self, app: &mut App) {
+        let (started_sender, started_receiver) = channel();
+        let (persisted_sender, persisted_receiver) = channel();
+        let (ended_sender, ended_receiver) = channel();
+        let (impulse_sender, impulse_receiver) = channel();
+
+        let touching = TouchingPairs::default();
+        let mut reporter = CollisionReporter {
+            touching: touching.clone(),
+            started: started_sender,
+            persisted: persisted_sender,
+            ended: ended_sender,
+            impulses: impulse_sender,
+        };
+
+        app.add_event_channel(started_receiver);
+        app.add_event_channel(persisted_receiver);
+        app.add_event_channel(ended_receiver);
+        app.add_event::<ContactForceEvent>();
+        app.init_resource::<Contacts>();
+        app.insert_resource(ContactImpulseReceiver(Mutex::new(impulse_receiver)));
+        app.insert_resource(ReporterTouchingPairs(touching));
+
+        app.register_type::<ContactEvents>();
+        app.register_type::<ContactForceThreshold>();
+        app.add_systems(PhysicsSchedule, (
+            contact_events_sync.after(shape_filter_data),
+            contact_force_threshold_sync,
+        ).in_set(PhysicsSet::Sync));
+        app.add_systems(PhysicsSchedule, (
+            contact_force_events,
+            collision_observers,
+            update_contacts,
+            forget_removed_shapes,
+        ).in_set(PhysicsSet::Create));
+
+        let mut builtin = app.world_mut().get_resource_or_init::<BuiltinCallbacks>();
+        builtin.filter_shader = true;
+        builtin.on_collision.push(Box::new(move |header, pairs| reporter.report(header, pairs)));
+    }
+}
+
+fn contact_events_sync(
+    mut filter_sync: ShapeFilterSync,
+    contact_events: Query<&ContactEvents>,
+    thresholds: Query<(), With<ContactForceThreshold>>,
+    changed: Query<Entity, Or<(
+        Added<ShapeHandle>,
+        Changed<ContactEvents>,
+        Changed<ShapeFilterData>,
+        Added<ContactForceThreshold>,
+    )>>,
+    mut removed: RemovedComponents<ContactEvents>,
+    mut removed_thresholds: RemovedComponents<ContactForceThreshold>,
+) {
+    let changed = changed.iter()
+        .chain(removed.read())
+        .chain(removed_thresholds.read());
+
+    filter_sync.update(changed, |filter| {
+        let mut bits = filter.get(&contact_events).map(|events| events.to_filter_bits()).unwrap_or(0);
+
+        if filter.actor.is_some_and(|actor| thresholds.contains(actor)) {
+            bits |= NOTIFY_FORCE_THRESHOLD;
+        }
+
+        filter.set_word3_bits(CONTACT_EVENTS_MASK, bits);
+    });
+}
+
+pub fn contact_force_threshold_sync(
+    mut scene: ResMut<Scene>,
+    mut actors: Query<
+        (Option<&mut RigidDynamicHandle>, Ref<ContactForceThreshold>),
+        Or<(Added<RigidDynamicHandle>, Changed<ContactForceThreshold>)>,
+    >,
+    mut removed: RemovedComponents<ContactForceThreshold>,
+    mut dynamics: Query<&mut RigidDynamicHandle, Without<ContactForceThreshold>>,
+) {
+    for (actor, threshold) in actors.iter_mut() {
+        if let Some(mut actor) = actor {
+            unsafe {
+                PxRigidDynamic_setContactReportThreshold_mut(actor.get_mut(&mut scene).as_mut_ptr(), threshold.0);
+            }
+        } else if !threshold.is_added() {
+            bevy::log::warn!("ContactForceThreshold component exists, but it's not a rigid dynamic");
+        }
+    }
+
+    for entity in removed.read() {
+        let Ok(mut actor) = dynamics.get_mut(entity) else { continue; };
+        unsafe {
+            PxRigidDynamic_setContactReportThreshold_mut(actor.get_mut(&mut scene).as_mut_ptr(), NO_CONTACT_FORCE_THRESHOLD);
+        }
+    }
+}
+
+fn contact_force_events(
+    receiver: Res<ContactImpulseReceiver>,
+    time: Res<PhysicsTime>,
+    mut events: EventWriter<ContactForceEvent>,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0. { return; }
+
+    for impulse in receiver.lock().unwrap().try_iter() {
+        events.write(ContactForceEvent {
+            actor0: impulse.actor0,
+            actor1: impulse.actor1,
+            shape0: impulse.shape0,
+            shape1: impulse.shape1,
+            total_normal_force: impulse.total_normal_impulse / dt,
+            max_impulse_point: impulse.max_impulse_point,
+        });
+    }
+}
+
+fn update_contacts(
+    mut contacts: ResMut<Contacts>,
+    mut started: EventReader<CollisionStarted>,
+    mut persisted: EventReader<CollisionPersisted>,
+    mut ended: EventReader<CollisionEnded>,
+    mut removed: RemovedComponents<ShapeHandle>,
+) {
+    for collision in started.read() {
+        contacts.insert(collision.0.clone());
+    }
+
+    for collision in persisted.read() {
+        contacts.update(collision);
+    }
+
+    for collision in ended.read() {
+        contacts.remove(collision.key());
+    }
+
+    // in case touch lost wasn't reported for a removed shape
+    for entity in removed.read() {
+        contacts.remove_entity(entity);
+    }
+}
+
+// touch lost for removed shapes is reported during simulation, after that
+// their pointers may be reused by new shapes
+fn forget_removed_shapes(
+    touching: Res<ReporterTouchingPairs>,
+    mut removed: RemovedComponents<ShapeHandle>,
+) {
+    let removed = removed.read().collect::<EntityHashSet>();
+    if removed.is_empty() { return; }
+
+    touching.lock().unwrap().retain(|_, collision| {
+        !removed.contains(&collision.shape0) && !removed.contains(&collision.shape1)
+    });
+}
+
+fn collision_observers(
+    mut commands: Commands,
+    mut started: EventReader<CollisionStarted>,
+    mut ended: EventReader<CollisionEnded>,
+    entities: Query<()>,
+) {
+    for collision in started.read() {
+        for (actor, shape, other_actor, other_shape) in collision.sides() {
+            trigger_observers(&mut commands, OnCollisionStart { shape, other_actor, other_shape }, actor, shape, &entities);
+        }
+    }
+
+    for collision in ended.read() {
+        for (actor, shape, other_actor, other_shape) in collision.sides() {
+            trigger_observers(&mut commands, OnCollisionEnd { shape, other_actor, other_shape }, actor, shape, &entities);
+        }
+    }
+}
+
+// actor and shape can be the same entity, and either of them can be already despawned
+pub(crate) fn trigger_observers<E: Event + Clone>(
+    commands: &mut Commands,
+    event: E,
+    actor: Entity,
+    shape: Entity,
+    entities: &Query<()>,
+) {
+    let mut targets = vec![];
+    if entities.contains(actor) { targets.push(actor); }
+    if shape != actor && entities.contains(shape) { targets.push(shape); }
+
+    // empty target list would trigger global observers instead
+    if targets.is_empty() { return; }
+    commands.trigger_targets(event, targets);
+}
+
+struct CollisionReporter {
+    // pairs of touching shapes, so we can report entities for removed shapes,
+    // whose pointers can no longer be dereferenced
+    touching: TouchingPairs,
+    started: Sender<CollisionStarted>,
+    persisted: Sender<CollisionPersisted>,
+    ended: Sender<CollisionEnded>,
+    impulses: Sender<ContactImpulse>,
+}
+
+impl CollisionReporter {
+    fn report(&mut self, header: &PxContactPairHeader, pairs: &[PxContactPair]) {
+        // SAFETY: called from collision callback of the scene with data passed by physx
+        let header = unsafe { ContactPairHeader::new(header) };
+
+        for pair in pairs.iter().map(|pair| unsafe { ContactPair::new(pair) }) {
+            let key = pair.shape_ptrs();
+
+            if pair.events().contains(PxPairFlags::NotifyTouchFound) {
+                let Some(collision) = collision_from_pair(&header, &pair) else { continue; };
+                if reports_touch_lost(&pair) {
+                    self.touching.lock().unwrap().insert(key, Collision { contacts: vec![], ..collision.clone() });
+                }
+                self.started.send(CollisionStarted(collision)).unwrap();
+            }
+
+            if pair.events().contains(PxPairFlags::NotifyTouchPersists) {
+                let Some(collision) = collision_from_pair(&header, &pair) else { continue; };
+                self.persisted.send(CollisionPersisted(collision)).unwrap();
+            }
+
+            if pair.events().contains(PxPairFlags::NotifyThresholdForceFound) {
+                let Some(collision) = collision_from_pair(&header, &pair) else { continue; };
+                let total_normal_impulse = collision.contacts.iter()
+                    .map(|contact| contact.impulse.dot(contact.normal).abs())
+                    .sum();
+                let max_impulse_point = collision.contacts.iter()
+                    .max_by(|a, b| a.impulse.length_squared().total_cmp(&b.impulse.length_squared()))
+                    .map(|contact| contact.position)
+                    .unwrap_or_default();
+
+                self.impulses.send(ContactImpulse {
+                    actor0: collision.actor0,
+                    actor1: collision.actor1,
+                    shape0: collision.shape0,
+                    shape1: collision.shape1,
+                    total_normal_impulse,
+                    max_impulse_point,
+                }).unwrap();
+            }
+
+            if pair.events().contains(PxPairFlags::NotifyTouchLost) {
+                let collision = self.touching.lock().unwrap().remove(&key)
+                    .or_else(|| collision_from_pair(&header, &pair).map(|c| Collision { contacts: vec![], ..c }));
+                let Some(collision) = collision else { continue; };
+                self.ended.send(CollisionEnded(collision)).unwrap();
+            }
+        }
+    }
+}
+
+// entries are only removed from touching pairs on touch lost (or shape removal),
+// so pairs that don't report it aren't tracked
+fn reports_touch_lost(pair: &ContactPair) -> bool {
+    let (shape0, shape1) = pair.shape_ptrs();
+    [ shape0, shape1 ].into_iter().any(|shape| {
+        // SAFETY: shapes of a pair that started touching can't be removed yet
+        let filter_data = unsafe { PxShape_getSimulationFilterData(shape as *const PxShape) };
+        filter_data.word3 & NOTIFY_TOUCH_LOST != 0
+    })
+}
+
+fn collision_from_pair(header: &ContactPairHeader, pair: &ContactPair) -> Option<Collision> {
+    let (actor0, actor1) = header.actors()?;
+    let (shape0, shape1) = pair.shapes()?;
+
+    Some(Collision {
+        actor0,
+        actor1,
+        shape0,
+        shape1,
+        contacts: pair.contacts().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::core::foundation::test_lock;
+
+    struct TestApp {
+        app: App,
+        // must be dropped after the app, which owns physics
+        _guard: std::sync::MutexGuard<'static, ()>,
+    }
+
+    impl TestApp {
+        fn new() -> Self {
+            let guard = test_lock();
+            let mut app = App::new();
+            app.add_plugins((
+                MinimalPlugins,
+                PhysicsCore::new()
+                    .with_timestep(TimestepMode::Custom)
+                    .with_gravity(Vec3::NEG_Y * 9.81),
+                CollisionEventsPlugin,
+            ));
+            app.finish();
+            app.cleanup();
+            Self { app, _guard: guard }
+        }
+
+        fn step(&mut self) {
+            let world = self.app.world_mut();
+            world.resource_mut::<PhysicsTime>().advance_by(Duration::from_secs_f32(1. / 60.));
+            world.run_schedule(PhysicsSchedule);
+        }
+
+        // ball resting on the ground, so they keep touching
+        fn spawn_ball_on_ground(&mut self) -> [Entity; 2] {
+            let world = self.app.world_mut();
+            let mut geometries = world.resource_mut::<Assets<Geometry>>();
+            let ground = geometries.add(Cuboid::new(10., 1., 10.));
+            let ball = geometries.add(Sphere::new(0.5));
+
+            [
+                (RigidBody::Static, ground, Vec3::new(0., -0.5, 0.)),
+                (RigidBody::Dynamic, ball, Vec3::new(0., 0.5, 0.)),
+            ].map(|(body, geometry, position)| {
+                world.spawn((
+                    body,
+                    Shape { geometry, ..default() },
+                    ContactEvents::default(),
+                    Transform::from_translation(position),
+                )).id()
+            })
+        }
+
+        fn events<E: Event + Clone>(&self) -> Vec<E> {
+            self.app.world().resource::<Events<E>>().iter_current_update_events().cloned().collect()
+        }
+
+        fn touching_pairs(&self) -> usize {
+            self.app.world().resource::<ReporterTouchingPairs>().lock().unwrap().len()
+        }
+    }
+
+    fn sorted(mut entities: [Entity; 2]) -> [Entity; 2] {
+        entities.sort();
+        entities
+    }
+
+    #[test]
+    fn collision_started_in_the_same_step() {
+        let mut app = TestApp::new();
+        let [ ground, ball ] = app.spawn_ball_on_ground();
+
+        // actors are created after simulation
+        app.step();
+        assert!(app.events::<CollisionStarted>().is_empty());
+
+        // events are sent right after the simulation step which reported them,
+        // so Contacts (updated in Create set) already has the pair
+        app.step();
+        let started = app.events::<CollisionStarted>();
+        assert_eq!(started.len(), 1);
+        assert_eq!(sorted([ started[0].actor0, started[0].actor1 ]), sorted([ ground, ball ]));
+        assert_eq!(sorted([ started[0].shape0, started[0].shape1 ]), sorted([ ground, ball ]));
+        assert!(app.app.world().resource::<Contacts>().is_touching(ground, ball));
+        assert_eq!(app.touching_pairs(), 1);
+
+        app.step();
+        assert_eq!(app.events::<CollisionStarted>().len(), 1);
+        assert!(app.events::<CollisionEnded>().is_empty());
+    }
+
+    #[test]
+    fn collision_ended_when_shape_is_despawned() {
+        let mut app = TestApp::new();
+        let [ ground, ball ] = app.spawn_ball_on_ground();
+        app.step();
+        app.step();
+        assert_eq!(app.touching_pairs(), 1);
+
+        app.app.world_mut().despawn(ball);
+        app.step();
+
+        let ended = app.events::<CollisionEnded>();
+        assert_eq!(ended.len(), 1);
+        assert_eq!(sorted([ ended[0].actor0, ended[0].actor1 ]), sorted([ ground, ball ]));
+        assert!(ended[0].contacts.is_empty());
+
+        // pointer of the removed shape may be reused, so the pair must be forgotten
+        assert_eq!(app.touching_pairs(), 0);
+        assert!(app.app.world().resource::<Contacts>().is_empty());
+    }
+
+    #[test]
+    fn no_events_without_contact_events() {
+        let mut app = TestApp::new();
+        let [ ground, ball ] = app.spawn_ball_on_ground();
+        for entity in [ ground, ball ] {
+            app.app.world_mut().entity_mut(entity).remove::<ContactEvents>();
+        }
+
+        app.step();
+        app.step();
+        assert!(app.events::<CollisionStarted>().is_empty());
+        assert_eq!(app.touching_pairs(), 0);
+    }
+}
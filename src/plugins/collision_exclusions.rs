@@ -0,0 +1,180 @@
+//! Disable collisions between specific pairs of actors.
+//!
+//! [CollisionExclusionsPlugin] installs crate filter shader (see [crate::core::filter]).
+//! Each actor that takes part in an exclusion gets an id, which is written to
+//! simulation filter data of its shapes, and filter callback of the scene rejects
+//! pairs of ids listed in [CollisionExclusions]. When the lists change (or listed
+//! actors are despawned), filtering is reset for shapes of affected actors, so
+//! pairs that are already touching are filtered again.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_mod_physx::prelude::*;
+//! fn spawn_vehicle(mut commands: Commands) {
+//!     let body = commands.spawn(RigidBody::Dynamic).id();
+//!     let wheels = [(); 4].map(|_| commands.spawn(RigidBody::Dynamic).id());
+//!     commands.entity(body).insert(CollisionExclusions(wheels.to_vec()));
+//! }
+//! ```
+use std::collections::HashSet;
+
+use bevy::ecs::entity::{Entities, EntityHashMap, EntityHashSet};
+use bevy::prelude::*;
+
+use crate::core::filter::{ExcludedPairs, ShapeFilterSync, EXCLUSION_ID_MASK, EXCLUSION_ID_SHIFT};
+use crate::core::scene::BuiltinCallbacks;
+use crate::plugins::shape_filter_data::shape_filter_data;
+use crate::prelude::{self as bpx, *};
+
+#[derive(Component, Debug, Default, PartialEq, Eq, Clone, Reflect, Deref, DerefMut)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+/// Actors this actor never collides with (e.g. a character and their weapon,
+/// or a vehicle and its own wheels).
+///
+/// Exclusions are symmetric, it's enough to list a pair on one side. They apply
+/// to all shapes of both actors (rigid dynamics, statics or articulation links),
+/// and only affect simulation, not scene queries.
+///
+/// Up to 65535 actors can take part in exclusions at the same time (ids of actors
+/// without exclusions are reused), exclusions of actors over this limit are ignored
+/// and reported as an error.
+pub struct CollisionExclusions(pub Vec<Entity>);
+
+impl CollisionExclusions {
+    pub fn new(entities: impl IntoIterator<Item = Entity>) -> Self {
+        Self(entities.into_iter().collect())
+    }
+}
+
+#[derive(Resource, Default)]
+struct ExclusionIds {
+    // actor -> id written to filter data of its shapes
+    ids: EntityHashMap<u32>,
+    free: Vec<u32>,
+    next: u32,
+    // excluded actor pairs, smaller entity first
+    pairs: HashSet<(Entity, Entity)>,
+}
+
+impl ExclusionIds {
+    fn allocate(&mut self) -> Option<u32> {
+        if let Some(id) = self.free.pop() { return Some(id); }
+        if self.next >= EXCLUSION_ID_MASK >> EXCLUSION_ID_SHIFT { return None; }
+        self.next += 1;
+        Some(self.next)
+    }
+}
+
+pub struct CollisionExclusionsPlugin;
+
+impl Plugin for CollisionExclusionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<CollisionExclusions>();
+        app.init_resource::<ExclusionIds>();
+        app.add_systems(PhysicsSchedule, collision_exclusions_sync
+            .after(shape_filter_data)
+            .in_set(PhysicsSet::Sync));
+
+        let mut builtin = app.world_mut().get_resource_or_init::<BuiltinCallbacks>();
+        builtin.filter_shader = true;
+    }
+}
+
+fn collision_exclusions_sync(
+    mut filter_sync: ShapeFilterSync,
+    mut state: ResMut<ExclusionIds>,
+    exclusions: Query<(Entity, &CollisionExclusions)>,
+    changed_exclusions: Query<(), Changed<CollisionExclusions>>,
+    changed: Query<Entity, Or<(Added<ShapeHandle>, Changed<ShapeFilterData>)>>,
+    entities: &Entities,
+    mut removed: RemovedComponents<CollisionExclusions>,
+    mut removed_actors: RemovedComponents<bpx::RigidBody>,
+) {
+    let mut dirty = changed.iter().collect::<EntityHashSet>();
+    // actors whose excluded pairs changed, need to be filtered again
+    let mut reset = EntityHashSet::default();
+
+    let any_removed = removed.read().count() > 0;
+    // pairs with despawned actors are dropped, and their ids are reused
+    let any_despawned = removed_actors.read().filter(|actor| state.ids.contains_key(actor)).count() > 0;
+
+    if any_removed || any_despawned || !changed_exclusions.is_empty() {
+        let pairs = exclusions.iter()
+            .flat_map(|(entity, list)| list.iter().map(move |other| (entity, *other)))
+            .filter(|(a, b)| a != b && entities.contains(*a) && entities.contains(*b))
+            .map(|(a, b)| (a.min(b), a.max(b)))
+            .collect::<HashSet<_>>();
+
+        for (a, b) in pairs.symmetric_difference(&state.pairs) {
+            reset.insert(*a);
+            reset.insert(*b);
+        }
+
+        let actors = pairs.iter().flat_map(|(a, b)| [ *a, *b ]).collect::<EntityHashSet>();
+        let ExclusionIds { ids, free, .. } = &mut *state;
+        ids.retain(|actor, id| {
+            let keep = actors.contains(actor);
+            if !keep { free.push(*id); }
+            keep
+        });
+
+        let mut ignored = 0;
+        for actor in actors {
+            if state.ids.contains_key(&actor) { continue; }
+            let Some(id) = state.allocate() else {
+                ignored += 1;
+                continue;
+            };
+            state.ids.insert(actor, id);
+        }
+
+        if ignored > 0 {
+            bevy::log::error!(
+                "too many actors with collision exclusions (at most {} are supported), exclusions of {ignored} actors are ignored",
+                EXCLUSION_ID_MASK >> EXCLUSION_ID_SHIFT,
+            );
+        }
+
+        let id_pairs = pairs.iter()
+            .filter_map(|(a, b)| Some((*state.ids.get(a)?, *state.ids.get(b)?)));
+
+        filter_sync.scene_mut().set_excluded_pairs(ExcludedPairs::new(id_pairs));
+        state.pairs = pairs;
+        dirty.extend(reset.iter().copied());
+    }
+
+    filter_sync.update(dirty, |filter| {
+        let id = filter.actor.and_then(|actor| state.ids.get(&actor)).copied().unwrap_or(0);
+        filter.set_word3_bits(EXCLUSION_ID_MASK, id << EXCLUSION_ID_SHIFT);
+        filter.reset_filtering = filter.actor.is_some_and(|actor| reset.contains(&actor));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exclusion_ids_are_recycled() {
+        let mut ids = ExclusionIds::default();
+        assert_eq!(ids.allocate(), Some(1));
+        assert_eq!(ids.allocate(), Some(2));
+
+        ids.free.push(1);
+        assert_eq!(ids.allocate(), Some(1));
+        assert_eq!(ids.allocate(), Some(3));
+    }
+
+    #[test]
+    fn exclusion_ids_run_out() {
+        let max = EXCLUSION_ID_MASK >> EXCLUSION_ID_SHIFT;
+        let mut ids = ExclusionIds { next: max - 1, ..default() };
+        assert_eq!(ids.allocate(), Some(max));
+        assert_eq!(ids.allocate(), None);
+
+        // ids of actors that no longer have exclusions can be used again
+        ids.free.push(5);
+        assert_eq!(ids.allocate(), Some(5));
+    }
+}
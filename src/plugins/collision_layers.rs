@@ -0,0 +1,230 @@
+//! Collision layers, to decide which shapes collide with each other without writing a filter shader.
+//!
+//! [CollisionLayersPlugin] installs crate filter shader (see [crate::core::filter]),
+//! which only lets two shapes collide if memberships of each of them share a bit
+//! with filters of the other one. Shapes without [CollisionLayers] are in all layers,
+//! and collide with all layers.
+//!
+//! Layers are only written to simulation filter data, query filter data stays as set by
+//! [ShapeFilterData]. Scene queries can use the same test with
+//! [SceneQueryFilter::with_collision_layers](crate::utils::raycast::SceneQueryFilter::with_collision_layers).
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_mod_physx::prelude::*;
+//! #[derive(PhysicsLayer)]
+//! enum Layer {
+//!     Ground,
+//!     Player,
+//!     Enemy,
+//! }
+//!
+//! fn spawn_player(mut commands: Commands) {
+//!     // players collide with ground and enemies, but not with each other
+//!     commands.spawn((
+//!         RigidBody::Dynamic,
+//!         CollisionLayers::from_layers([ Layer::Player ], [ Layer::Ground, Layer::Enemy ]),
+//!     ));
+//! }
+//! ```
+use bevy::prelude::*;
+
+use crate::core::filter::{ShapeFilterSync, COLLISION_LAYERS};
+use crate::core::scene::BuiltinCallbacks;
+use crate::plugins::shape_filter_data::shape_filter_data;
+use crate::prelude::*;
+
+/// Layer (or a set of layers) that can be used in [CollisionLayers].
+///
+/// Each layer is a bit in a 32-bit mask. Usually derived for a fieldless enum
+/// (with `derive` feature, enabled by default), where each variant is a layer
+/// `1 << index` in declaration order:
+///
+/// ```
+/// # use bevy_mod_physx::prelude::*;
+/// #[derive(PhysicsLayer)]
+/// enum Layer {
+///     Player,
+///     Enemy,
+/// }
+///
+/// assert_eq!(CollisionLayers::new(Layer::Enemy, Layer::Player), CollisionLayers::new(0b10, 0b01));
+/// ```
+///
+/// Or implemented manually:
+///
+/// ```
+/// # use bevy_mod_physx::prelude::*;
+/// #[derive(Clone, Copy)]
+/// enum Layer {
+///     Player,
+///     Enemy,
+/// }
+///
+/// impl PhysicsLayer for Layer {
+///     fn to_bits(&self) -> u32 {
+///         1 << *self as u32
+///     }
+/// }
+/// ```
+pub trait PhysicsLayer {
+    fn to_bits(&self) -> u32;
+}
+
+#[cfg(feature = "derive")]
+pub use bevy_mod_physx_derive::PhysicsLayer;
+
+impl PhysicsLayer for u32 {
+    fn to_bits(&self) -> u32 {
+        *self
+    }
+}
+
+impl<L: PhysicsLayer> PhysicsLayer for &L {
+    fn to_bits(&self) -> u32 {
+        (*self).to_bits()
+    }
+}
+
+#[derive(Component, Debug, PartialEq, Eq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+/// Collision layers of an actor (all its shapes) or a single shape.
+///
+/// Two shapes collide if `(a.memberships & b.filters) != 0 && (b.memberships & a.filters) != 0`.
+///
+/// Layers are stored in words 0 and 1 of simulation filter data, overwriting values
+/// from [ShapeFilterData::simulation_filter_data] for these words. Query filter data
+/// is not affected, so [SceneQueryFilter::with_filter_data](crate::utils::raycast::SceneQueryFilter::with_filter_data)
+/// keeps matching [ShapeFilterData::query_filter_data].
+pub struct CollisionLayers {
+    /// Layers this shape belongs to.
+    pub memberships: u32,
+    /// Layers this shape collides with.
+    pub filters: u32,
+}
+
+impl CollisionLayers {
+    /// Belongs to all layers, and collides with all layers.
+    pub const ALL: Self = Self { memberships: u32::MAX, filters: u32::MAX };
+    /// Belongs to no layers, and doesn't collide with anything.
+    pub const NONE: Self = Self { memberships: 0, filters: 0 };
+
+    pub fn new(memberships: impl PhysicsLayer, filters: impl PhysicsLayer) -> Self {
+        Self { memberships: memberships.to_bits(), filters: filters.to_bits() }
+    }
+
+    pub fn from_layers<L: PhysicsLayer>(
+        memberships: impl IntoIterator<Item = L>,
+        filters: impl IntoIterator<Item = L>,
+    ) -> Self {
+        Self { memberships: layer_bits(memberships), filters: layer_bits(filters) }
+    }
+
+    pub fn with_memberships(mut self, memberships: impl PhysicsLayer) -> Self {
+        self.memberships = memberships.to_bits();
+        self
+    }
+
+    pub fn with_filters(mut self, filters: impl PhysicsLayer) -> Self {
+        self.filters = filters.to_bits();
+        self
+    }
+
+    /// Whether shapes with these layers collide with shapes with `other` layers.
+    pub fn interacts_with(&self, other: &Self) -> bool {
+        self.memberships & other.filters != 0 && other.memberships & self.filters != 0
+    }
+}
+
+fn layer_bits<L: PhysicsLayer>(layers: impl IntoIterator<Item = L>) -> u32 {
+    layers.into_iter().fold(0, |bits, layer| bits | layer.to_bits())
+}
+
+impl Default for CollisionLayers {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+pub struct CollisionLayersPlugin;
+
+impl Plugin for CollisionLayersPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<CollisionLayers>();
+        app.add_systems(PhysicsSchedule, collision_layers_sync
+            .after(shape_filter_data)
+            .in_set(PhysicsSet::Sync));
+
+        let mut builtin = app.world_mut().get_resource_or_init::<BuiltinCallbacks>();
+        builtin.filter_shader = true;
+    }
+}
+
+fn collision_layers_sync(
+    mut filter_sync: ShapeFilterSync,
+    layers: Query<&CollisionLayers>,
+    user_data: Query<&ShapeFilterData>,
+    changed: Query<Entity, Or<(Added<ShapeHandle>, Changed<CollisionLayers>, Changed<ShapeFilterData>)>>,
+    mut removed: RemovedComponents<CollisionLayers>,
+) {
+    filter_sync.update(changed.iter().chain(removed.read()), |filter| {
+        let layers = filter.get(&layers).copied();
+        let user_data = user_data.get(filter.shape).copied().unwrap_or_default();
+
+        let words = &mut filter.simulation_filter_data;
+
+        // when layers are removed, words 0 and 1 are given back to ShapeFilterData
+        if let Some(layers) = layers {
+            words[0] = layers.memberships;
+            words[1] = layers.filters;
+            words[3] |= COLLISION_LAYERS;
+        } else if words[3] & COLLISION_LAYERS != 0 {
+            words[0] = user_data.simulation_filter_data[0];
+            words[1] = user_data.simulation_filter_data[1];
+            words[3] &= !COLLISION_LAYERS;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::filter::layers_interact;
+
+    #[derive(Clone, Copy, PhysicsLayer)]
+    enum Layer {
+        Ground,
+        Player,
+        Enemy,
+    }
+
+    #[test]
+    fn layers_from_enum() {
+        let player = CollisionLayers::from_layers([ Layer::Player ], [ Layer::Ground, Layer::Enemy ]);
+        assert_eq!(player, CollisionLayers::new(0b010, 0b101));
+        assert_eq!(CollisionLayers::new(Layer::Enemy, &Layer::Ground), CollisionLayers::new(0b100, 0b001));
+    }
+
+    #[test]
+    fn interacts_with_matches_filter_shader() {
+        let ground = CollisionLayers::new(Layer::Ground, Layer::Player);
+        let player = CollisionLayers::from_layers([ Layer::Player ], [ Layer::Ground, Layer::Enemy ]);
+        let enemy = CollisionLayers::from_layers([ Layer::Enemy ], [ Layer::Ground, Layer::Player, Layer::Enemy ]);
+
+        assert!(ground.interacts_with(&player));
+        assert!(player.interacts_with(&enemy));
+        assert!(!player.interacts_with(&player));
+        assert!(!ground.interacts_with(&enemy));
+
+        let all = [ ground, player, enemy, CollisionLayers::ALL, CollisionLayers::NONE ];
+        let words = |layers: CollisionLayers| [ layers.memberships, layers.filters, 0, COLLISION_LAYERS ];
+
+        for a in all {
+            for b in all {
+                assert_eq!(a.interacts_with(&b), b.interacts_with(&a));
+                assert_eq!(a.interacts_with(&b), layers_interact(words(a), words(b)));
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Send [ConstraintBroken] events when breakable joints break.
+//!
+//! Only joints created by this crate (see [crate::plugins::joint]) are reported, they are
+//! recognized by their PhysX name, which shouldn't be changed. Articulation joints
+//! can't break in PhysX, so they never send this event.
+use std::sync::mpsc::channel;
+
+use bevy::prelude::*;
+use physx_sys::{PxConstraintExtIDs, PxConstraint_getActors, PxJoint, PxRigidActor};
+
+use crate::core::scene::BuiltinCallbacks;
+use crate::prelude::*;
+use crate::utils::{get_actor_entity_from_ptr, get_joint_entity_from_ptr};
+
+/// A breakable joint was broken, because force or torque exceeded its limits.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstraintBroken {
+    pub joint: Entity,
+    /// First body, `None` if the joint is attached to the world.
+    pub body0: Option<Entity>,
+    /// Second body, `None` if the joint is attached to the world.
+    pub body1: Option<Entity>,
+}
+
+pub struct ConstraintBreakPlugin;
+
+impl Plugin for ConstraintBreakPlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = channel();
+
+        app.add_event_channel(receiver);
+
+        let mut builtin = app.world_mut().get_resource_or_init::<BuiltinCallbacks>();
+        builtin.on_constraint_break.push(Box::new(move |constraints| {
+            for info in constraints {
+                if info.type_ != PxConstraintExtIDs::Joint as u32 { continue; }
+
+                // SAFETY: external reference of a joint constraint is the joint itself
+                let Some(joint) = (unsafe { get_joint_entity_from_ptr(info.externalReference as *const PxJoint) }) else { continue; };
+
+                let mut actor0: *mut PxRigidActor = std::ptr::null_mut();
+                let mut actor1: *mut PxRigidActor = std::ptr::null_mut();
+                unsafe { PxConstraint_getActors(info.constraint, &mut actor0, &mut actor1) };
+
+                // SAFETY: actors are created by this crate, and can't be removed during simulation
+                let body = |actor: *mut PxRigidActor| {
+                    (!actor.is_null()).then(|| unsafe { get_actor_entity_from_ptr(actor) })
+                };
+
+                sender.send(ConstraintBroken { joint, body0: body(actor0), body1: body(actor1) }).unwrap();
+            }
+        }));
+    }
+}
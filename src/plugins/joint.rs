@@ -0,0 +1,994 @@
+//! Joints between two rigid actors (or an actor and the world), built on PhysX extension joints.
+//!
+//! Unlike articulations, joints don't need to form a tree, so they can be used
+//! for loops and for attaching bodies to the world. Joint is an entity with [Joint]
+//! component (bodies and local frames), and one of the joint types: [FixedJoint],
+//! [RevoluteJoint], [PrismaticJoint], [SphericalJoint], [DistanceJoint] or [D6Joint].
+//!
+//! Joint is created in [PhysicsSet::Create] once both actors exist, and released
+//! when it's despawned or when either actor goes away. Changing bodies or joint type
+//! re-creates the joint, other settings are applied at runtime.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_mod_physx::prelude::*;
+//! fn spawn_door(mut commands: Commands) {
+//!     let door = commands.spawn(RigidBody::Dynamic).id();
+//!
+//!     // hinge attached to the world at the edge of the door
+//!     commands.spawn((
+//!         Joint::new(None, Some(door))
+//!             .with_frames(Transform::from_xyz(1., 1., 0.), Transform::from_xyz(0.5, 0., 0.)),
+//!         RevoluteJoint::default(),
+//!     ));
+//! }
+//! ```
+use std::ffi::c_void;
+use std::ptr::{null_mut, NonNull};
+
+use bevy::prelude::*;
+use physx::traits::Class;
+use physx_sys::{
+    phys_PxD6JointCreate,
+    phys_PxDistanceJointCreate,
+    phys_PxFixedJointCreate,
+    phys_PxPrismaticJointCreate,
+    phys_PxRevoluteJointCreate,
+    phys_PxSphericalJointCreate,
+    PxConstraintFlag,
+    PxD6Axis,
+    PxD6Drive,
+    PxD6Joint,
+    PxD6JointDrive,
+    PxD6JointDrive_new_1,
+    PxD6Joint_setDrivePosition_mut,
+    PxD6Joint_setDriveVelocity_mut,
+    PxD6Joint_setDrive_mut,
+    PxD6Joint_setLinearLimit_mut,
+    PxD6Joint_setMotion_mut,
+    PxD6Joint_setPyramidSwingLimit_mut,
+    PxD6Joint_setTwistLimit_mut,
+    PxD6Motion,
+    PxDistanceJoint,
+    PxDistanceJointFlag,
+    PxDistanceJoint_setDamping_mut,
+    PxDistanceJoint_setDistanceJointFlag_mut,
+    PxDistanceJoint_setMaxDistance_mut,
+    PxDistanceJoint_setMinDistance_mut,
+    PxDistanceJoint_setStiffness_mut,
+    PxJoint,
+    PxJointActorIndex,
+    PxJointAngularLimitPair,
+    PxJointAngularLimitPair_new_1,
+    PxJointLimitCone,
+    PxJointLimitCone_new_1,
+    PxJointLinearLimitPair,
+    PxJointLinearLimitPair_new_1,
+    PxJointLimitPyramid_new_1,
+    PxJoint_release_mut,
+    PxJoint_setBreakForce_mut,
+    PxJoint_setConstraintFlag_mut,
+    PxJoint_setLocalPose_mut,
+    PxJoint_setName_mut,
+    PxPrismaticJoint,
+    PxPrismaticJointFlag,
+    PxPrismaticJoint_setLimit_mut,
+    PxPrismaticJoint_setPrismaticJointFlag_mut,
+    PxRevoluteJoint,
+    PxRevoluteJointFlag,
+    PxRevoluteJoint_setDriveForceLimit_mut,
+    PxRevoluteJoint_setDriveVelocity_mut,
+    PxRevoluteJoint_setLimit_mut,
+    PxRevoluteJoint_setRevoluteJointFlag_mut,
+    PxRigidActor,
+    PxSphericalJoint,
+    PxSphericalJointFlag,
+    PxSphericalJoint_setLimitCone_mut,
+    PxSphericalJoint_setSphericalJointFlag_mut,
+    PxSpring,
+    PxSpring_new,
+};
+
+use crate::core::scene::SceneRwLock;
+use crate::prelude::{self as bpx, Scene, *};
+use crate::utils::JOINT_NAME;
+
+#[derive(Component, Debug, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+/// Bodies connected by a joint, and joint frames relative to each of them.
+///
+/// Joint type is set by a separate component, see [crate::plugins::joint].
+pub struct Joint {
+    /// First actor, `None` attaches the joint to the world.
+    pub body_a: Option<Entity>,
+    /// Second actor, `None` attaches the joint to the world.
+    pub body_b: Option<Entity>,
+    /// Joint frame relative to the first actor (or the world), scale is ignored.
+    pub frame_a: Transform,
+    /// Joint frame relative to the second actor (or the world), scale is ignored.
+    pub frame_b: Transform,
+    /// Force that breaks the joint, sending [ConstraintBroken] event.
+    ///
+    /// Default: f32::MAX (unbreakable)
+    pub break_force: f32,
+    /// Torque that breaks the joint, sending [ConstraintBroken] event.
+    ///
+    /// Default: f32::MAX (unbreakable)
+    pub break_torque: f32,
+    /// Let connected bodies collide with each other.
+    pub collide_connected: bool,
+}
+
+impl Joint {
+    pub fn new(body_a: Option<Entity>, body_b: Option<Entity>) -> Self {
+        Self { body_a, body_b, ..default() }
+    }
+
+    pub fn with_frames(mut self, frame_a: Transform, frame_b: Transform) -> Self {
+        self.frame_a = frame_a;
+        self.frame_b = frame_b;
+        self
+    }
+
+    pub fn with_break_force(mut self, force: f32, torque: f32) -> Self {
+        self.break_force = force;
+        self.break_torque = torque;
+        self
+    }
+
+    pub fn with_collide_connected(mut self, collide_connected: bool) -> Self {
+        self.collide_connected = collide_connected;
+        self
+    }
+}
+
+impl Default for Joint {
+    fn default() -> Self {
+        Self {
+            body_a: None,
+            body_b: None,
+            frame_a: default(),
+            frame_b: default(),
+            break_force: f32::MAX,
+            break_torque: f32::MAX,
+            collide_connected: false,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+/// Pair of joint limits (angles in radians, or distances).
+///
+/// Limit is hard if both stiffness and damping are zero, and acts as a spring otherwise.
+pub struct JointLimit {
+    pub lower: f32,
+    pub upper: f32,
+    pub stiffness: f32,
+    pub damping: f32,
+}
+
+impl JointLimit {
+    pub fn new(lower: f32, upper: f32) -> Self {
+        Self { lower, upper, ..default() }
+    }
+
+    pub fn with_spring(mut self, stiffness: f32, damping: f32) -> Self {
+        self.stiffness = stiffness;
+        self.damping = damping;
+        self
+    }
+
+    fn spring(&self) -> PxSpring {
+        unsafe { PxSpring_new(self.stiffness, self.damping) }
+    }
+
+    fn to_angular(self) -> PxJointAngularLimitPair {
+        unsafe { PxJointAngularLimitPair_new_1(self.lower, self.upper, &self.spring()) }
+    }
+
+    fn to_linear(self) -> PxJointLinearLimitPair {
+        unsafe { PxJointLinearLimitPair_new_1(self.lower, self.upper, &self.spring()) }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+/// Elliptical cone limit, angles are in radians around Y and Z axes of the joint frame.
+///
+/// Limit is hard if both stiffness and damping are zero, and acts as a spring otherwise.
+pub struct JointLimitCone {
+    pub y_angle: f32,
+    pub z_angle: f32,
+    pub stiffness: f32,
+    pub damping: f32,
+}
+
+impl JointLimitCone {
+    pub fn new(y_angle: f32, z_angle: f32) -> Self {
+        Self { y_angle, z_angle, ..default() }
+    }
+
+    pub fn with_spring(mut self, stiffness: f32, damping: f32) -> Self {
+        self.stiffness = stiffness;
+        self.damping = damping;
+        self
+    }
+
+    fn to_physx(self) -> PxJointLimitCone {
+        let spring = unsafe { PxSpring_new(self.stiffness, self.damping) };
+        unsafe { PxJointLimitCone_new_1(self.y_angle, self.z_angle, &spring) }
+    }
+}
+
+#[derive(Component, Debug, Default, PartialEq, Eq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+#[require(Joint)]
+/// Joint that locks all relative motion between two bodies.
+pub struct FixedJoint;
+
+#[derive(Component, Debug, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+#[require(Joint)]
+/// Hinge joint, allows rotation around X axis of the joint frames.
+pub struct RevoluteJoint {
+    /// Limit of the rotation angle.
+    pub limit: Option<JointLimit>,
+    /// Target angular velocity of the drive, `None` disables the drive.
+    pub drive_velocity: Option<f32>,
+    /// Maximum torque the drive can apply.
+    ///
+    /// Default: f32::MAX
+    pub drive_force_limit: f32,
+    /// Drive only accelerates the joint, and lets it spin faster than the target velocity.
+    pub drive_free_spin: bool,
+}
+
+impl Default for RevoluteJoint {
+    fn default() -> Self {
+        Self {
+            limit: None,
+            drive_velocity: None,
+            drive_force_limit: f32::MAX,
+            drive_free_spin: false,
+        }
+    }
+}
+
+#[derive(Component, Debug, Default, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+#[require(Joint)]
+/// Slider joint, allows translation along X axis of the joint frames.
+pub struct PrismaticJoint {
+    /// Limit of the translation.
+    pub limit: Option<JointLimit>,
+}
+
+#[derive(Component, Debug, Default, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+#[require(Joint)]
+/// Ball-and-socket joint, allows free rotation around the joint origin.
+pub struct SphericalJoint {
+    /// Limit of X axis of the second frame within a cone around X axis of the first frame.
+    pub limit: Option<JointLimitCone>,
+}
+
+#[derive(Component, Debug, Default, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+#[require(Joint)]
+/// Keeps distance between origins of the joint frames within limits.
+pub struct DistanceJoint {
+    pub min_distance: Option<f32>,
+    pub max_distance: Option<f32>,
+    /// Spring stiffness when distance exceeds the limits (hard limits if stiffness and damping are zero).
+    pub stiffness: f32,
+    pub damping: f32,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+/// Motion of a single [D6Joint] axis.
+pub enum D6JointMotion {
+    #[default]
+    Locked,
+    Free,
+    /// Limited motion, angles for rotational axes are in radians.
+    ///
+    /// PhysX limits both swing axes together with a single spring, so spring of
+    /// the limit is ignored (with a warning) for swing1 and swing2, see [D6Joint::swing_limit_stiffness].
+    Limited(JointLimit),
+}
+
+#[derive(Component, Debug, Default, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+#[require(Joint)]
+/// Configurable joint, with motion set separately for each axis of the joint frames.
+///
+/// Twist is rotation around X axis, swing1 is rotation around Y axis,
+/// and swing2 is rotation around Z axis. All axes are locked by default.
+///
+/// Drives are configured with [D6JointDrives], [D6JointDriveTarget]
+/// and [D6JointDriveVelocity].
+pub struct D6Joint {
+    pub x: D6JointMotion,
+    pub y: D6JointMotion,
+    pub z: D6JointMotion,
+    pub twist: D6JointMotion,
+    pub swing1: D6JointMotion,
+    pub swing2: D6JointMotion,
+    /// Spring stiffness of swing1 and swing2 limits, shared by both axes
+    /// (hard limits if stiffness and damping are zero).
+    pub swing_limit_stiffness: f32,
+    pub swing_limit_damping: f32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+/// Drive of a single [D6Joint] axis, a spring pulling it towards [D6JointDriveTarget]
+/// and [D6JointDriveVelocity].
+///
+/// Drive is disabled if both stiffness and damping are zero (see [D6JointDrive::DISABLED]).
+pub struct D6JointDrive {
+    pub stiffness: f32,
+    pub damping: f32,
+    /// Maximum force (or torque) the drive can apply.
+    pub force_limit: f32,
+    /// Drive is an acceleration rather than a force, so it's independent of body masses.
+    pub is_acceleration: bool,
+}
+
+impl D6JointDrive {
+    /// Drive that doesn't apply any force, used for axes not set in [D6JointDrives].
+    pub const DISABLED: Self = Self::new(0., 0.);
+
+    /// Drive with unlimited force.
+    pub const fn new(stiffness: f32, damping: f32) -> Self {
+        Self { stiffness, damping, force_limit: f32::MAX, is_acceleration: false }
+    }
+
+    pub fn with_force_limit(mut self, force_limit: f32) -> Self {
+        self.force_limit = force_limit;
+        self
+    }
+
+    pub fn with_acceleration(mut self, is_acceleration: bool) -> Self {
+        self.is_acceleration = is_acceleration;
+        self
+    }
+
+    fn to_physx(self) -> PxD6JointDrive {
+        unsafe { PxD6JointDrive_new_1(self.stiffness, self.damping, self.force_limit, self.is_acceleration) }
+    }
+}
+
+#[derive(Component, Debug, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+/// Configures a [D6Joint] drive for each axis, all drives are disabled by default.
+///
+/// Rotational drives are either swing and twist (used if twist, swing1 and swing2
+/// axes are not all free), or slerp (used if they are).
+pub struct D6JointDrives {
+    pub x: D6JointDrive,
+    pub y: D6JointDrive,
+    pub z: D6JointDrive,
+    pub swing: D6JointDrive,
+    pub twist: D6JointDrive,
+    pub slerp: D6JointDrive,
+}
+
+impl Default for D6JointDrives {
+    fn default() -> Self {
+        Self {
+            x: D6JointDrive::DISABLED,
+            y: D6JointDrive::DISABLED,
+            z: D6JointDrive::DISABLED,
+            swing: D6JointDrive::DISABLED,
+            twist: D6JointDrive::DISABLED,
+            slerp: D6JointDrive::DISABLED,
+        }
+    }
+}
+
+#[derive(Component, Debug, Default, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+/// Sets the [D6Joint] drive target, a pose of the second joint frame relative to the first one.
+pub struct D6JointDriveTarget {
+    pub pose: Transform,
+}
+
+#[derive(Component, Debug, Default, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+/// Sets the [D6Joint] drive target velocity, relative to the first joint frame.
+pub struct D6JointDriveVelocity {
+    pub linear: Vec3,
+    pub angular: Vec3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JointKind {
+    Fixed,
+    Revolute,
+    Prismatic,
+    Spherical,
+    Distance,
+    D6,
+}
+
+#[derive(Component, Deref, DerefMut)]
+pub struct JointHandle {
+    #[deref]
+    handle: SceneRwLock<NonNull<PxJoint>>,
+    // settings the joint was created with, joint is re-created when they change
+    bodies: [Option<Entity>; 2],
+    kind: JointKind,
+}
+
+// SAFETY: joint is only accessed through SceneRwLock, same as actors
+unsafe impl Send for JointHandle {}
+unsafe impl Sync for JointHandle {}
+
+impl Drop for JointHandle {
+    fn drop(&mut self) {
+        // SAFETY: joint is owned by this handle
+        unsafe { PxJoint_release_mut(self.handle.get_mut_unsafe().as_ptr()) };
+    }
+}
+
+pub struct JointPlugin;
+
+impl Plugin for JointPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Joint>();
+        app.register_type::<FixedJoint>();
+        app.register_type::<RevoluteJoint>();
+        app.register_type::<PrismaticJoint>();
+        app.register_type::<SphericalJoint>();
+        app.register_type::<DistanceJoint>();
+        app.register_type::<D6Joint>();
+        app.register_type::<D6JointDrives>();
+        app.register_type::<D6JointDriveTarget>();
+        app.register_type::<D6JointDriveVelocity>();
+
+        app.add_systems(PhysicsSchedule, (
+            joint_sync,
+            revolute_joint_sync,
+            prismatic_joint_sync,
+            spherical_joint_sync,
+            distance_joint_sync,
+            d6_joint_sync,
+            d6_joint_drives_sync,
+            d6_joint_drive_target_sync,
+            d6_joint_drive_velocity_sync,
+        ).in_set(PhysicsSet::Sync));
+
+        app.add_systems(PhysicsSchedule, (
+            release_joints,
+            create_joints,
+        ).chain().in_set(PhysicsSet::Create));
+    }
+}
+
+type ActorHandles<'a> = (
+    Option<&'a RigidDynamicHandle>,
+    Option<&'a RigidStaticHandle>,
+    Option<&'a ArticulationLinkHandle>,
+);
+
+fn get_actor_ptr(actors: &Query<ActorHandles>, entity: Entity) -> Option<*mut PxRigidActor> {
+    let (dynamic, fixed, link) = actors.get(entity).ok()?;
+
+    // SAFETY: caller holds scene write lock, and pointer is only passed to physx
+    unsafe {
+        if let Some(actor) = dynamic {
+            Some(actor.get_unsafe().as_ptr() as *mut PxRigidActor)
+        } else if let Some(actor) = fixed {
+            Some(actor.get_unsafe().as_ptr() as *mut PxRigidActor)
+        } else {
+            link.map(|actor| actor.get_unsafe().as_ptr() as *mut PxRigidActor)
+        }
+    }
+}
+
+fn get_joint_kind(
+    (fixed, revolute, prismatic, spherical, distance, d6): (bool, bool, bool, bool, bool, bool),
+) -> Option<JointKind> {
+    let kinds = [
+        (fixed, JointKind::Fixed),
+        (revolute, JointKind::Revolute),
+        (prismatic, JointKind::Prismatic),
+        (spherical, JointKind::Spherical),
+        (distance, JointKind::Distance),
+        (d6, JointKind::D6),
+    ];
+
+    let mut found = kinds.iter().filter(|(has, _)| *has).map(|(_, kind)| *kind);
+    let kind = found.next()?;
+    if found.next().is_some() { return None; }
+    Some(kind)
+}
+
+type JointKindQuery = (
+    Has<FixedJoint>,
+    Has<RevoluteJoint>,
+    Has<PrismaticJoint>,
+    Has<SphericalJoint>,
+    Has<DistanceJoint>,
+    Has<D6Joint>,
+);
+
+// settings components only apply to joints of their kind; handle of another kind is either
+// waiting to be re-created with the right kind (settings are applied once it's created),
+// or joint type doesn't match the settings, which is reported
+fn matches_kind(handle: &JointHandle, kinds: (bool, bool, bool, bool, bool, bool), expected: JointKind, component: &str) -> bool {
+    if handle.kind == expected { return true; }
+    if get_joint_kind(kinds) != Some(expected) {
+        bevy::log::warn!("{component} component exists, but it's not a {expected:?} joint");
+    }
+    false
+}
+
+pub fn release_joints(
+    mut commands: Commands,
+    joints: Query<(Entity, &JointHandle, Option<&Joint>, JointKindQuery)>,
+    actors: Query<ActorHandles>,
+) {
+    for (entity, handle, joint, kinds) in joints.iter() {
+        let bodies_gone = handle.bodies.iter().flatten()
+            .any(|body| get_actor_ptr(&actors, *body).is_none());
+
+        let reconfigured = joint.is_none_or(|joint| [ joint.body_a, joint.body_b ] != handle.bodies)
+            || get_joint_kind(kinds) != Some(handle.kind);
+
+        if bodies_gone || reconfigured {
+            // dropping the handle releases the joint
+            commands.entity(entity).remove::<JointHandle>();
+        }
+    }
+}
+
+pub fn create_joints(
+    mut commands: Commands,
+    mut physics: ResMut<bpx::Physics>,
+    mut scene: ResMut<Scene>,
+    new_joints: Query<(Entity, Ref<Joint>, JointKindQuery), Without<JointHandle>>,
+    actors: Query<ActorHandles>,
+) {
+    for (entity, joint, kinds) in new_joints.iter() {
+        let Some(kind) = get_joint_kind(kinds) else {
+            if joint.is_changed() {
+                bevy::log::warn!("Joint component exists, but it has no joint type or multiple joint types");
+            }
+            continue;
+        };
+
+        if joint.body_a.is_none() && joint.body_b.is_none() {
+            if joint.is_changed() {
+                bevy::log::warn!("Joint component exists, but both of its bodies are set to world");
+            }
+            continue;
+        }
+
+        let _scene = scene.get_mut();
+
+        // wait until both actors are created
+        let actor = |body: Option<Entity>| match body {
+            Some(body) => get_actor_ptr(&actors, body),
+            None => Some(null_mut()),
+        };
+        let (Some(actor_a), Some(actor_b)) = (actor(joint.body_a), actor(joint.body_b)) else { continue; };
+
+        let physics = physics.physics_mut().as_mut_ptr();
+        let frame_a = joint.frame_a.to_physx_sys();
+        let frame_b = joint.frame_b.to_physx_sys();
+
+        let ptr = unsafe {
+            match kind {
+                JointKind::Fixed => {
+                    phys_PxFixedJointCreate(physics, actor_a, &frame_a, actor_b, &frame_b) as *mut PxJoint
+                }
+                JointKind::Revolute => {
+                    phys_PxRevoluteJointCreate(physics, actor_a, &frame_a, actor_b, &frame_b) as *mut PxJoint
+                }
+                JointKind::Prismatic => {
+                    phys_PxPrismaticJointCreate(physics, actor_a, &frame_a, actor_b, &frame_b) as *mut PxJoint
+                }
+                JointKind::Spherical => {
+                    phys_PxSphericalJointCreate(physics, actor_a, &frame_a, actor_b, &frame_b) as *mut PxJoint
+                }
+                JointKind::Distance => {
+                    phys_PxDistanceJointCreate(physics, actor_a, &frame_a, actor_b, &frame_b) as *mut PxJoint
+                }
+                JointKind::D6 => {
+                    phys_PxD6JointCreate(physics, actor_a, &frame_a, actor_b, &frame_b) as *mut PxJoint
+                }
+            }
+        };
+
+        let Some(ptr) = NonNull::new(ptr) else {
+            // creation is retried every tick, but only reported once per change
+            if joint.is_changed() {
+                bevy::log::warn!("failed to create joint {entity}");
+            }
+            continue;
+        };
+
+        unsafe {
+            // entity is stored in userdata directly, see get_joint_entity_from_ptr
+            (*ptr.as_ptr()).userData = entity.to_bits() as usize as *mut c_void;
+            PxJoint_setName_mut(ptr.as_ptr(), JOINT_NAME.as_ptr());
+        }
+
+        commands.entity(entity).insert(JointHandle {
+            handle: SceneRwLock::new(ptr),
+            bodies: [ joint.body_a, joint.body_b ],
+            kind,
+        });
+    }
+}
+
+pub fn joint_sync(
+    mut scene: ResMut<Scene>,
+    mut joints: Query<(&mut JointHandle, &Joint), Or<(Added<JointHandle>, Changed<Joint>)>>,
+) {
+    // this function only applies user defined properties,
+    // there's nothing to get back from physx engine
+    for (mut handle, joint) in joints.iter_mut() {
+        let handle = handle.get_mut(&mut scene);
+        let ptr = handle.as_ptr();
+        let frame_a = joint.frame_a.to_physx_sys();
+        let frame_b = joint.frame_b.to_physx_sys();
+
+        unsafe {
+            PxJoint_setLocalPose_mut(ptr, PxJointActorIndex::Actor0, &frame_a);
+            PxJoint_setLocalPose_mut(ptr, PxJointActorIndex::Actor1, &frame_b);
+            PxJoint_setBreakForce_mut(ptr, joint.break_force, joint.break_torque);
+            PxJoint_setConstraintFlag_mut(ptr, PxConstraintFlag::CollisionEnabled, joint.collide_connected);
+        }
+    }
+}
+
+pub fn revolute_joint_sync(
+    mut scene: ResMut<Scene>,
+    mut joints: Query<
+        (&mut JointHandle, &RevoluteJoint, JointKindQuery),
+        Or<(Added<JointHandle>, Changed<RevoluteJoint>)>,
+    >,
+) {
+    for (mut handle, settings, kinds) in joints.iter_mut() {
+        if !matches_kind(&handle, kinds, JointKind::Revolute, "RevoluteJoint") { continue; }
+        let handle = handle.get_mut(&mut scene);
+        let joint = handle.as_ptr() as *mut PxRevoluteJoint;
+
+        unsafe {
+            if let Some(limit) = settings.limit {
+                PxRevoluteJoint_setLimit_mut(joint, &limit.to_angular());
+            }
+            PxRevoluteJoint_setRevoluteJointFlag_mut(joint, PxRevoluteJointFlag::LimitEnabled, settings.limit.is_some());
+
+            if let Some(velocity) = settings.drive_velocity {
+                PxRevoluteJoint_setDriveVelocity_mut(joint, velocity, true);
+            }
+            PxRevoluteJoint_setDriveForceLimit_mut(joint, settings.drive_force_limit);
+            PxRevoluteJoint_setRevoluteJointFlag_mut(joint, PxRevoluteJointFlag::DriveEnabled, settings.drive_velocity.is_some());
+            PxRevoluteJoint_setRevoluteJointFlag_mut(joint, PxRevoluteJointFlag::DriveFreespin, settings.drive_free_spin);
+        }
+    }
+}
+
+pub fn prismatic_joint_sync(
+    mut scene: ResMut<Scene>,
+    mut joints: Query<
+        (&mut JointHandle, &PrismaticJoint, JointKindQuery),
+        Or<(Added<JointHandle>, Changed<PrismaticJoint>)>,
+    >,
+) {
+    for (mut handle, settings, kinds) in joints.iter_mut() {
+        if !matches_kind(&handle, kinds, JointKind::Prismatic, "PrismaticJoint") { continue; }
+        let handle = handle.get_mut(&mut scene);
+        let joint = handle.as_ptr() as *mut PxPrismaticJoint;
+
+        unsafe {
+            if let Some(limit) = settings.limit {
+                PxPrismaticJoint_setLimit_mut(joint, &limit.to_linear());
+            }
+            PxPrismaticJoint_setPrismaticJointFlag_mut(joint, PxPrismaticJointFlag::LimitEnabled, settings.limit.is_some());
+        }
+    }
+}
+
+pub fn spherical_joint_sync(
+    mut scene: ResMut<Scene>,
+    mut joints: Query<
+        (&mut JointHandle, &SphericalJoint, JointKindQuery),
+        Or<(Added<JointHandle>, Changed<SphericalJoint>)>,
+    >,
+) {
+    for (mut handle, settings, kinds) in joints.iter_mut() {
+        if !matches_kind(&handle, kinds, JointKind::Spherical, "SphericalJoint") { continue; }
+        let handle = handle.get_mut(&mut scene);
+        let joint = handle.as_ptr() as *mut PxSphericalJoint;
+
+        unsafe {
+            if let Some(limit) = settings.limit {
+                PxSphericalJoint_setLimitCone_mut(joint, &limit.to_physx());
+            }
+            PxSphericalJoint_setSphericalJointFlag_mut(joint, PxSphericalJointFlag::LimitEnabled, settings.limit.is_some());
+        }
+    }
+}
+
+pub fn distance_joint_sync(
+    mut scene: ResMut<Scene>,
+    mut joints: Query<
+        (&mut JointHandle, &DistanceJoint, JointKindQuery),
+        Or<(Added<JointHandle>, Changed<DistanceJoint>)>,
+    >,
+) {
+    for (mut handle, settings, kinds) in joints.iter_mut() {
+        if !matches_kind(&handle, kinds, JointKind::Distance, "DistanceJoint") { continue; }
+        let handle = handle.get_mut(&mut scene);
+        let joint = handle.as_ptr() as *mut PxDistanceJoint;
+
+        unsafe {
+            if let Some(distance) = settings.min_distance {
+                PxDistanceJoint_setMinDistance_mut(joint, distance);
+            }
+            if let Some(distance) = settings.max_distance {
+                PxDistanceJoint_setMaxDistance_mut(joint, distance);
+            }
+            PxDistanceJoint_setStiffness_mut(joint, settings.stiffness);
+            PxDistanceJoint_setDamping_mut(joint, settings.damping);
+
+            let spring = settings.stiffness > 0. || settings.damping > 0.;
+            PxDistanceJoint_setDistanceJointFlag_mut(joint, PxDistanceJointFlag::MinDistanceEnabled, settings.min_distance.is_some());
+            PxDistanceJoint_setDistanceJointFlag_mut(joint, PxDistanceJointFlag::MaxDistanceEnabled, settings.max_distance.is_some());
+            PxDistanceJoint_setDistanceJointFlag_mut(joint, PxDistanceJointFlag::SpringEnabled, spring);
+        }
+    }
+}
+
+pub fn d6_joint_sync(
+    mut scene: ResMut<Scene>,
+    mut joints: Query<
+        (Entity, &mut JointHandle, &D6Joint, JointKindQuery),
+        Or<(Added<JointHandle>, Changed<D6Joint>)>,
+    >,
+) {
+    for (entity, mut handle, settings, kinds) in joints.iter_mut() {
+        if !matches_kind(&handle, kinds, JointKind::D6, "D6Joint") { continue; }
+        let handle = handle.get_mut(&mut scene);
+        let joint = handle.as_ptr() as *mut PxD6Joint;
+
+        fn set_motion(joint: *mut PxD6Joint, axis: PxD6Axis, value: D6JointMotion) {
+            let motion = match value {
+                D6JointMotion::Locked => PxD6Motion::Locked,
+                D6JointMotion::Free => PxD6Motion::Free,
+                D6JointMotion::Limited(limit) => {
+                    match axis {
+                        PxD6Axis::X | PxD6Axis::Y | PxD6Axis::Z => unsafe {
+                            PxD6Joint_setLinearLimit_mut(joint, axis, &limit.to_linear());
+                        },
+                        PxD6Axis::Twist => unsafe {
+                            PxD6Joint_setTwistLimit_mut(joint, &limit.to_angular());
+                        },
+                        // swing limits are set together below
+                        _ => {}
+                    }
+                    PxD6Motion::Limited
+                }
+            };
+
+            unsafe { PxD6Joint_setMotion_mut(joint, axis, motion) };
+        }
+
+        set_motion(joint, PxD6Axis::X, settings.x);
+        set_motion(joint, PxD6Axis::Y, settings.y);
+        set_motion(joint, PxD6Axis::Z, settings.z);
+        set_motion(joint, PxD6Axis::Twist, settings.twist);
+        set_motion(joint, PxD6Axis::Swing1, settings.swing1);
+        set_motion(joint, PxD6Axis::Swing2, settings.swing2);
+
+        // swing limits are a pyramid, so each axis gets its own range;
+        // range of an axis that is not limited is ignored by physx
+        let limit = |motion: D6JointMotion| if let D6JointMotion::Limited(limit) = motion { Some(limit) } else { None };
+        let swing1 = limit(settings.swing1);
+        let swing2 = limit(settings.swing2);
+
+        let has_spring = |limit: Option<JointLimit>| limit.is_some_and(|limit| limit.stiffness != 0. || limit.damping != 0.);
+        if has_spring(swing1) || has_spring(swing2) {
+            bevy::log::warn!("D6Joint {entity}: spring of swing1 and swing2 limits is ignored, use swing_limit_stiffness and swing_limit_damping instead");
+        }
+
+        if swing1.is_some() || swing2.is_some() {
+            let full = JointLimit::new(-std::f32::consts::PI, std::f32::consts::PI);
+            let swing1 = swing1.unwrap_or(full);
+            let swing2 = swing2.unwrap_or(full);
+
+            unsafe {
+                let spring = PxSpring_new(settings.swing_limit_stiffness, settings.swing_limit_damping);
+                let pyramid = PxJointLimitPyramid_new_1(swing1.lower, swing1.upper, swing2.lower, swing2.upper, &spring);
+                PxD6Joint_setPyramidSwingLimit_mut(joint, &pyramid);
+            }
+        }
+    }
+}
+
+pub fn d6_joint_drives_sync(
+    mut scene: ResMut<Scene>,
+    mut joints: Query<
+        (&mut JointHandle, &D6JointDrives, JointKindQuery),
+        Or<(Added<JointHandle>, Changed<D6JointDrives>)>,
+    >,
+) {
+    // this function only applies user defined properties,
+    // there's nothing to get back from physx engine
+    for (mut handle, drives, kinds) in joints.iter_mut() {
+        if !matches_kind(&handle, kinds, JointKind::D6, "D6JointDrives") { continue; }
+
+        let handle = handle.get_mut(&mut scene);
+        let joint = handle.as_ptr() as *mut PxD6Joint;
+
+        fn set_drive(joint: *mut PxD6Joint, index: PxD6Drive, value: D6JointDrive) {
+            unsafe { PxD6Joint_setDrive_mut(joint, index, &value.to_physx()) };
+        }
+
+        set_drive(joint, PxD6Drive::X, drives.x);
+        set_drive(joint, PxD6Drive::Y, drives.y);
+        set_drive(joint, PxD6Drive::Z, drives.z);
+        set_drive(joint, PxD6Drive::Swing, drives.swing);
+        set_drive(joint, PxD6Drive::Twist, drives.twist);
+        set_drive(joint, PxD6Drive::Slerp, drives.slerp);
+    }
+}
+
+pub fn d6_joint_drive_target_sync(
+    mut scene: ResMut<Scene>,
+    mut joints: Query<
+        (&mut JointHandle, &D6JointDriveTarget, JointKindQuery),
+        Or<(Added<JointHandle>, Changed<D6JointDriveTarget>)>,
+    >,
+) {
+    // this function only applies user defined properties,
+    // there's nothing to get back from physx engine
+    for (mut handle, target, kinds) in joints.iter_mut() {
+        if !matches_kind(&handle, kinds, JointKind::D6, "D6JointDriveTarget") { continue; }
+
+        let handle = handle.get_mut(&mut scene);
+        let joint = handle.as_ptr() as *mut PxD6Joint;
+        let pose = target.pose.to_physx_sys();
+
+        unsafe { PxD6Joint_setDrivePosition_mut(joint, &pose, true) };
+    }
+}
+
+pub fn d6_joint_drive_velocity_sync(
+    mut scene: ResMut<Scene>,
+    mut joints: Query<
+        (&mut JointHandle, &D6JointDriveVelocity, JointKindQuery),
+        Or<(Added<JointHandle>, Changed<D6JointDriveVelocity>)>,
+    >,
+) {
+    // this function only applies user defined properties,
+    // there's nothing to get back from physx engine
+    for (mut handle, velocity, kinds) in joints.iter_mut() {
+        if !matches_kind(&handle, kinds, JointKind::D6, "D6JointDriveVelocity") { continue; }
+
+        let handle = handle.get_mut(&mut scene);
+        let joint = handle.as_ptr() as *mut PxD6Joint;
+        let linear = velocity.linear.to_physx_sys();
+        let angular = velocity.angular.to_physx_sys();
+
+        unsafe { PxD6Joint_setDriveVelocity_mut(joint, &linear, &angular, true) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::core::foundation::test_lock;
+
+    struct TestApp {
+        app: App,
+        // must be dropped after the app, which owns physics
+        _guard: std::sync::MutexGuard<'static, ()>,
+    }
+
+    impl TestApp {
+        fn new() -> Self {
+            let guard = test_lock();
+            let mut app = App::new();
+            app.add_plugins((
+                MinimalPlugins,
+                PhysicsCore::new().with_timestep(TimestepMode::Custom),
+                JointPlugin,
+            ));
+            app.finish();
+            app.cleanup();
+            Self { app, _guard: guard }
+        }
+
+        fn step(&mut self) {
+            let world = self.app.world_mut();
+            world.resource_mut::<PhysicsTime>().advance_by(Duration::from_secs_f32(1. / 60.));
+            world.run_schedule(PhysicsSchedule);
+        }
+
+        fn joint_kind(&self, entity: Entity) -> Option<JointKind> {
+            self.app.world().get::<JointHandle>(entity).map(|handle| handle.kind)
+        }
+    }
+
+    fn spawn_bodies(app: &mut TestApp) -> [Entity; 2] {
+        [ Vec3::ZERO, Vec3::X ].map(|position| {
+            app.app.world_mut().spawn((RigidBody::Dynamic, Transform::from_translation(position))).id()
+        })
+    }
+
+    #[test]
+    fn get_joint_kind_requires_one_type() {
+        assert_eq!(get_joint_kind((false, true, false, false, false, false)), Some(JointKind::Revolute));
+        assert_eq!(get_joint_kind((false, false, false, false, false, true)), Some(JointKind::D6));
+        assert_eq!(get_joint_kind((false, false, false, false, false, false)), None);
+        assert_eq!(get_joint_kind((true, false, false, false, true, false)), None);
+    }
+
+    #[test]
+    fn create_joint() {
+        let mut app = TestApp::new();
+        let [ a, b ] = spawn_bodies(&mut app);
+        let joint = app.app.world_mut().spawn((Joint::new(Some(a), Some(b)), RevoluteJoint::default())).id();
+
+        // actors are created first, joint waits for them
+        app.step();
+        app.step();
+        assert_eq!(app.joint_kind(joint), Some(JointKind::Revolute));
+    }
+
+    #[test]
+    fn release_joint_when_body_is_despawned() {
+        let mut app = TestApp::new();
+        let [ a, b ] = spawn_bodies(&mut app);
+        let joint = app.app.world_mut().spawn((Joint::new(Some(a), Some(b)), FixedJoint)).id();
+        app.step();
+        app.step();
+        assert_eq!(app.joint_kind(joint), Some(JointKind::Fixed));
+
+        app.app.world_mut().despawn(b);
+        app.step();
+        assert_eq!(app.joint_kind(joint), None);
+    }
+
+    #[test]
+    fn recreate_joint_when_reconfigured() {
+        let mut app = TestApp::new();
+        let [ a, b ] = spawn_bodies(&mut app);
+        let joint = app.app.world_mut().spawn((Joint::new(Some(a), Some(b)), RevoluteJoint::default())).id();
+        app.step();
+        app.step();
+        assert_eq!(app.joint_kind(joint), Some(JointKind::Revolute));
+
+        // old joint is released in one tick, and the new one is created in the next
+        app.app.world_mut().entity_mut(joint).remove::<RevoluteJoint>().insert(PrismaticJoint::default());
+        app.step();
+        app.step();
+        assert_eq!(app.joint_kind(joint), Some(JointKind::Prismatic));
+
+        app.app.world_mut().get_mut::<Joint>(joint).unwrap().body_a = None;
+        app.step();
+        app.step();
+        let handle = app.app.world().get::<JointHandle>(joint).unwrap();
+        assert_eq!(handle.bodies, [ None, Some(b) ]);
+    }
+}
@@ -4,15 +4,26 @@
 //! their own similar plugins.
 //!
 pub mod articulation;
+pub mod collision_events;
+pub mod collision_exclusions;
+pub mod collision_layers;
+pub mod constraint_break;
 pub mod damping;
 #[cfg(feature = "debug-render")]
 pub mod debug_render;
 pub mod external_force;
+pub mod joint;
 pub mod kinematic;
 pub mod mass_properties;
+pub mod modify_contacts;
 pub mod name;
+pub mod one_way_platform;
+#[cfg(feature = "picking")]
+pub mod picking;
 pub mod shape_filter_data;
 pub mod shape_offsets;
 pub mod sleep;
+pub mod surface_velocity;
+pub mod trigger;
 pub mod velocity;
 pub mod lock_flags;
@@ -0,0 +1,45 @@
+//! Request contact modification for an actor or a shape.
+use bevy::prelude::*;
+
+use crate::core::filter::{ShapeFilterSync, MODIFY_CONTACTS};
+use crate::plugins::shape_filter_data::shape_filter_data;
+use crate::prelude::*;
+
+#[derive(Component, Debug, Default, PartialEq, Eq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+/// Pass contacts of an actor (all its shapes) or a single shape through
+/// registered contact modifiers, see [crate::core::contact_modify].
+pub struct ModifyContacts;
+
+pub struct ModifyContactsPlugin;
+
+impl Plugin for ModifyContactsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ModifyContacts>();
+        app.add_systems(PhysicsSchedule, modify_contacts_sync
+            .after(shape_filter_data)
+            .in_set(PhysicsSet::Sync));
+    }
+}
+
+fn modify_contacts_sync(
+    mut filter_sync: ShapeFilterSync,
+    modify: Query<&ModifyContacts>,
+    changed: Query<Entity, Or<(Added<ShapeHandle>, Added<ModifyContacts>, Changed<ShapeFilterData>)>>,
+    mut removed: RemovedComponents<ModifyContacts>,
+) {
+    // this plugin is in the default group, so `word3` of shapes without
+    // ModifyContacts is left as is (it may be used by a custom filter shader)
+    filter_sync.update(changed.iter(), |filter| {
+        if filter.get(&modify).is_some() {
+            filter.set_word3_bits(MODIFY_CONTACTS, MODIFY_CONTACTS);
+        }
+    });
+
+    filter_sync.update(removed.read(), |filter| {
+        if filter.get(&modify).is_none() {
+            filter.set_word3_bits(MODIFY_CONTACTS, 0);
+        }
+    });
+}
@@ -0,0 +1,132 @@
+//! One-way platforms, which bodies can pass through from one side, and land on from the other.
+//!
+//! [OneWayPlatformPlugin] registers a contact modifier (see [crate::core::contact_modify]),
+//! which ignores contacts with a platform unless all contact normals point along
+//! [OneWayPlatform::normal], and the body isn't moving away from the platform along it.
+//!
+//! The direction is decided when a body starts touching the platform: if it comes
+//! from the wrong side (or from the edge), it keeps passing through for as long as
+//! it's touching the platform, even if its center is already above it. Once a body
+//! lands, it stays on the platform until it stops touching it.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_mod_physx::prelude::*;
+//! fn spawn_platform(mut commands: Commands) {
+//!     commands.spawn((RigidBody::Static, OneWayPlatform { normal: Dir3::Y }));
+//! }
+//! ```
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use bevy::ecs::entity::EntityHashMap;
+use bevy::prelude::*;
+
+use crate::core::scene::BuiltinCallbacks;
+use crate::prelude::*;
+
+#[derive(Component, Debug, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+#[require(ModifyContacts)]
+/// Make an actor (all its shapes) or a single shape a one-way platform.
+///
+/// Intended for static and kinematic actors.
+pub struct OneWayPlatform {
+    /// Side of the platform bodies can land on, relative to the actor.
+    pub normal: Dir3,
+}
+
+impl Default for OneWayPlatform {
+    fn default() -> Self {
+        Self { normal: Dir3::Y }
+    }
+}
+
+#[derive(Default)]
+struct PlatformState {
+    // entity with OneWayPlatform (actor or shape) -> normal relative to actor
+    platforms: RwLock<EntityHashMap<Vec3>>,
+    // shape pairs touching a platform -> last step they touched, and whether they pass through
+    touching: Mutex<HashMap<(Entity, Entity), (u32, bool)>>,
+    step: AtomicU32,
+}
+
+impl ContactModifier for PlatformState {
+    fn modify_contacts(&self, pair: &mut ContactModifyPair) {
+        let platforms = self.platforms.read().unwrap();
+        if platforms.is_empty() { return; }
+
+        let find = |shape, actor| platforms.get(&shape).or_else(|| platforms.get(&actor)).copied();
+
+        // contact normals point from shape1 to shape0, flip them so they point
+        // from the platform to the other body
+        let (normal, transform, sign) = if let Some(normal) = find(pair.shape1(), pair.actor1()) {
+            (normal, pair.transform1(), 1.)
+        } else if let Some(normal) = find(pair.shape0(), pair.actor0()) {
+            (normal, pair.transform0(), -1.)
+        } else {
+            return;
+        };
+
+        drop(platforms);
+
+        let normal = transform.rotation * normal;
+        let key = (pair.shape0(), pair.shape1());
+        let step = self.step.load(Ordering::Relaxed);
+
+        let mut touching = self.touching.lock().unwrap();
+        let passes = match touching.get(&key) {
+            Some((last, passes)) if *last + 1 >= step => *passes,
+            _ => {
+                let lands = pair.contacts().all(|contact| (contact.normal() * sign).dot(normal) > 0.);
+                // velocity of the other body relative to the platform
+                let velocity = (pair.linear_velocity0() - pair.linear_velocity1()) * sign;
+                !lands || velocity.dot(normal) > 0.
+            }
+        };
+
+        touching.insert(key, (step, passes));
+        if passes { pair.ignore_all(); }
+    }
+}
+
+#[derive(Resource, Deref)]
+struct OneWayPlatforms(Arc<PlatformState>);
+
+pub struct OneWayPlatformPlugin;
+
+impl Plugin for OneWayPlatformPlugin {
+    fn build(&self, app: &mut App) {
+        let state = Arc::new(PlatformState::default());
+
+        app.register_type::<OneWayPlatform>();
+        app.insert_resource(OneWayPlatforms(state.clone()));
+        app.add_systems(PhysicsSchedule, one_way_platform_sync.in_set(PhysicsSet::Sync));
+
+        let mut builtin = app.world_mut().get_resource_or_init::<BuiltinCallbacks>();
+        builtin.contact_modifiers.push(state);
+    }
+}
+
+fn one_way_platform_sync(
+    state: Res<OneWayPlatforms>,
+    platforms: Query<(Entity, &OneWayPlatform), Changed<OneWayPlatform>>,
+    mut removed: RemovedComponents<OneWayPlatform>,
+) {
+    // this runs once per simulation step, pairs not touched during last step are no longer touching
+    let step = state.step.fetch_add(1, Ordering::Relaxed) + 1;
+    state.touching.lock().unwrap().retain(|_, (last, _)| *last + 1 >= step);
+
+    let mut removed = removed.read().peekable();
+    if platforms.is_empty() && removed.peek().is_none() { return; }
+
+    let mut map = state.platforms.write().unwrap();
+    for entity in removed {
+        map.remove(&entity);
+    }
+    for (entity, platform) in platforms.iter() {
+        map.insert(entity, platform.normal.as_vec3());
+    }
+}
@@ -0,0 +1,133 @@
+//! Picking backend for `bevy_picking`, which raycasts through the PhysX scene.
+//!
+//! Every pointer ray of an active camera is cast into the scene, and the closest
+//! hit is reported with the shape entity. If shapes are children of their actor, picking events
+//! bubble up to the actor as usual. Render meshes are not required.
+//!
+//! Shapes are filtered with [PhysicsPickingSettings::filter_data] (matched against
+//! [ShapeFilterData::query_filter_data]) and [Pickable] component, which is looked
+//! up on the shape entity first, then on its actor. Shapes with
+//! `Pickable::is_hoverable` set to `false` are ignored.
+//!
+//! To make picking opt-in, set [PhysicsPickingSettings::require_markers] to `true`,
+//! and add [PhysicsPickingCamera] and [Pickable] components to the desired cameras
+//! and entities.
+//!
+//! This plugin is not included in [PhysicsPlugins], add it separately:
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_mod_physx::prelude::*;
+//! App::new()
+//!     .add_plugins(DefaultPlugins)
+//!     .add_plugins(PhysicsPlugins)
+//!     .add_plugins(PhysicsPickingPlugin);
+//! ```
+use bevy::ecs::entity::EntityHashMap;
+use bevy::picking::backend::ray::RayMap;
+use bevy::picking::backend::{HitData, PointerHits};
+use bevy::picking::{PickSet, Pickable};
+use bevy::prelude::*;
+
+use crate::prelude::*;
+use crate::utils::raycast::{QueryHitType, SceneQueryFilter};
+
+/// Marks cameras used by [PhysicsPickingPlugin].
+///
+/// Only needed if [PhysicsPickingSettings::require_markers] is set to `true`.
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+pub struct PhysicsPickingCamera;
+
+#[derive(Resource, Debug, Clone, Copy, Reflect)]
+#[reflect(Resource, Default)]
+/// Runtime settings for [PhysicsPickingPlugin].
+pub struct PhysicsPickingSettings {
+    /// Only use cameras marked with [PhysicsPickingCamera], and only pick
+    /// shapes (or actors) marked with [Pickable].
+    ///
+    /// Default: false
+    pub require_markers: bool,
+    /// Query filter data for picking rays, see
+    /// [SceneQueryFilter::with_filter_data](crate::utils::raycast::SceneQueryFilter::with_filter_data).
+    ///
+    /// Default: all zeroes (no filtering)
+    pub filter_data: [u32; 4],
+    /// Maximum distance of picking rays.
+    ///
+    /// Default: f32::MAX
+    pub max_distance: f32,
+}
+
+impl Default for PhysicsPickingSettings {
+    fn default() -> Self {
+        Self {
+            require_markers: false,
+            filter_data: [0; 4],
+            max_distance: f32::MAX,
+        }
+    }
+}
+
+pub struct PhysicsPickingPlugin;
+
+impl Plugin for PhysicsPickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<PhysicsPickingCamera>();
+        app.register_type::<PhysicsPickingSettings>();
+        app.init_resource::<PhysicsPickingSettings>();
+        app.add_systems(PreUpdate, update_hits.in_set(PickSet::Backend));
+    }
+}
+
+pub fn update_hits(
+    settings: Res<PhysicsPickingSettings>,
+    ray_map: Res<RayMap>,
+    cameras: Query<(&Camera, Has<PhysicsPickingCamera>)>,
+    pickables: Query<(Entity, &Pickable)>,
+    changed_pickables: Query<(), Changed<Pickable>>,
+    mut removed_pickables: RemovedComponents<Pickable>,
+    spatial_query: SpatialQuery,
+    mut filter: Local<Option<SceneQueryFilter>>,
+    mut output: EventWriter<PointerHits>,
+) {
+    let pickables_changed = removed_pickables.read().count() > 0 || !changed_pickables.is_empty();
+    if filter.is_none() || pickables_changed || settings.is_changed() {
+        *filter = Some(picking_filter(&settings, &pickables));
+    }
+    let Some(filter) = filter.as_ref() else { return; };
+
+    for (ray_id, &ray) in ray_map.iter() {
+        let Ok((camera, is_marked)) = cameras.get(ray_id.camera) else { continue; };
+        if !camera.is_active { continue; }
+        if settings.require_markers && !is_marked { continue; }
+
+        let Some(hit) = spatial_query.raycast(ray, settings.max_distance, filter) else { continue; };
+
+        let hit_data = HitData::new(ray_id.camera, hit.distance, Some(hit.position), Some(hit.normal));
+        output.write(PointerHits::new(ray_id.pointer, vec![(hit.shape, hit_data)], camera.order as f32));
+    }
+}
+
+// query predicate can't borrow from the world, so it gets a copy of pickable state,
+// which is only rebuilt when Pickable components or settings change
+fn picking_filter(settings: &PhysicsPickingSettings, pickables: &Query<(Entity, &Pickable)>) -> SceneQueryFilter {
+    let hoverable = pickables.iter()
+        .map(|(entity, pickable)| (entity, pickable.is_hoverable))
+        .collect::<EntityHashMap<_>>();
+
+    let filter = if settings.require_markers || !hoverable.is_empty() {
+        let require_markers = settings.require_markers;
+        SceneQueryFilter::with_predicate(move |actor, shape, _| {
+            let is_hoverable = hoverable.get(&shape)
+                .or_else(|| hoverable.get(&actor))
+                .copied()
+                .unwrap_or(!require_markers);
+
+            if is_hoverable { QueryHitType::Block } else { QueryHitType::None }
+        })
+    } else {
+        SceneQueryFilter::new()
+    };
+
+    filter.with_filter_data(settings.filter_data)
+}
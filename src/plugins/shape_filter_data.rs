@@ -15,7 +15,14 @@ use crate::prelude::{Scene, *};
 #[reflect(Component, Default)]
 /// Set the shape's query and collision filter data.
 pub struct ShapeFilterData {
+    /// Filter data used by scene queries.
+    ///
+    /// Matched against query filter data, see [SceneQueryFilter::with_filter_data](crate::utils::raycast::SceneQueryFilter::with_filter_data).
     pub query_filter_data: [ u32; 4 ],
+    /// Filter data passed to the simulation filter shader.
+    ///
+    /// If crate filter shader is used (see [crate::core::filter]), last word is
+    /// reserved, and overwritten by components like [ContactEvents](crate::plugins::collision_events::ContactEvents).
     pub simulation_filter_data: [ u32; 4 ],
 }
 
@@ -16,6 +16,7 @@ use physx_sys::{
     PxArticulationReducedCoordinate_wakeUp_mut,
 };
 
+use crate::core::scene::BuiltinCallbacks;
 use crate::types::OnWakeSleep;
 use crate::prelude::{Scene, *};
 
@@ -29,9 +30,6 @@ use crate::prelude::{Scene, *};
 /// wake up an actor, use [SleepControl] instead.
 pub struct Sleeping;
 
-#[derive(Resource)]
-pub(crate) struct WakeSleepCallback(pub(crate) OnWakeSleep);
-
 #[derive(Event)]
 pub struct WakeSleepEvent {
     pub entities: Vec<Entity>,
@@ -83,7 +81,7 @@ impl Plugin for SleepPlugin {
 
         let (wake_sleep_sender, wake_sleep_receiver) = channel();
 
-        app.world_mut().insert_resource(WakeSleepCallback(OnWakeSleep::new(move |actors, is_waking| {
+        app.world_mut().get_resource_or_init::<BuiltinCallbacks>().on_wake_sleep = Some(OnWakeSleep::new(move |actors, is_waking| {
             let entities = actors.iter().map(|actor| {
                 actor.cast_map(
                     |articulation| *articulation.get_user_data(),
@@ -93,7 +91,7 @@ impl Plugin for SleepPlugin {
             }).collect::<Vec<_>>();
 
             wake_sleep_sender.send(WakeSleepEvent { entities, is_waking }).unwrap();
-        })));
+        }));
 
         app.add_physics_event_channel(wake_sleep_receiver);
     }
@@ -102,7 +100,7 @@ impl Plugin for SleepPlugin {
         // Resource shall be consumed when creating physics scene.
         // If it doesn't, it means sleep plugin is loaded after scene is created,
         // which shouldn't happen.
-        assert!(!app.world().contains_resource::<WakeSleepCallback>());
+        assert!(!app.world().contains_resource::<BuiltinCallbacks>());
     }
 }
 
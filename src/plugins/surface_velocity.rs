@@ -0,0 +1,124 @@
+//! Surface velocity, for conveyor belts and similar surfaces that move without moving the actor.
+//!
+//! [SurfaceVelocityPlugin] registers a contact modifier (see [crate::core::contact_modify]),
+//! which sets target velocity of contacts with the surface, so friction drags
+//! touching bodies along.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_mod_physx::prelude::*;
+//! fn spawn_conveyor(mut commands: Commands) {
+//!     commands.spawn((RigidBody::Static, SurfaceVelocity::linear(Vec3::X * 2.)));
+//! }
+//! ```
+use std::sync::{Arc, RwLock};
+
+use bevy::ecs::entity::EntityHashMap;
+use bevy::prelude::*;
+
+use crate::core::scene::BuiltinCallbacks;
+use crate::prelude::*;
+
+#[derive(Component, Debug, Default, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+#[require(ModifyContacts)]
+/// Velocity of the surface of an actor (all its shapes) or a single shape,
+/// without the actor itself moving.
+///
+/// Intended for static and kinematic actors. Both velocities are relative to the actor,
+/// angular velocity rotates the surface around actor origin.
+pub struct SurfaceVelocity {
+    pub linear: Vec3,
+    pub angular: Vec3,
+}
+
+impl SurfaceVelocity {
+    pub fn new(linear: Vec3, angular: Vec3) -> Self {
+        Self { linear, angular }
+    }
+
+    pub fn linear(linear: Vec3) -> Self {
+        Self { linear, ..default() }
+    }
+
+    pub fn angular(angular: Vec3) -> Self {
+        Self { angular, ..default() }
+    }
+}
+
+#[derive(Default)]
+struct SurfaceState {
+    // entity with SurfaceVelocity (actor or shape) -> velocity
+    surfaces: RwLock<EntityHashMap<SurfaceVelocity>>,
+}
+
+impl ContactModifier for SurfaceState {
+    fn modify_contacts(&self, pair: &mut ContactModifyPair) {
+        let surfaces = self.surfaces.read().unwrap();
+        if surfaces.is_empty() { return; }
+
+        let find = |shape, actor| surfaces.get(&shape).or_else(|| surfaces.get(&actor)).copied();
+
+        // target velocity is velocity of the first body relative to the second one,
+        // so surface velocity is flipped if the surface is the first body
+        let (surface, transform, sign) = if let Some(surface) = find(pair.shape1(), pair.actor1()) {
+            (surface, pair.transform1(), 1.)
+        } else if let Some(surface) = find(pair.shape0(), pair.actor0()) {
+            (surface, pair.transform0(), -1.)
+        } else {
+            return;
+        };
+
+        drop(surfaces);
+
+        let linear = transform.rotation * surface.linear;
+        let angular = transform.rotation * surface.angular;
+
+        for mut contact in pair.contacts() {
+            let velocity = linear + angular.cross(contact.point() - transform.translation);
+
+            // solver only uses tangential part for friction
+            let normal = contact.normal();
+            let tangential = velocity - normal * velocity.dot(normal);
+
+            let target = contact.target_velocity() + tangential * sign;
+            contact.set_target_velocity(target);
+        }
+    }
+}
+
+#[derive(Resource, Deref)]
+struct SurfaceVelocities(Arc<SurfaceState>);
+
+pub struct SurfaceVelocityPlugin;
+
+impl Plugin for SurfaceVelocityPlugin {
+    fn build(&self, app: &mut App) {
+        let state = Arc::new(SurfaceState::default());
+
+        app.register_type::<SurfaceVelocity>();
+        app.insert_resource(SurfaceVelocities(state.clone()));
+        app.add_systems(PhysicsSchedule, surface_velocity_sync.in_set(PhysicsSet::Sync));
+
+        let mut builtin = app.world_mut().get_resource_or_init::<BuiltinCallbacks>();
+        builtin.contact_modifiers.push(state);
+    }
+}
+
+fn surface_velocity_sync(
+    state: Res<SurfaceVelocities>,
+    surfaces: Query<(Entity, &SurfaceVelocity), Changed<SurfaceVelocity>>,
+    mut removed: RemovedComponents<SurfaceVelocity>,
+) {
+    let mut removed = removed.read().peekable();
+    if surfaces.is_empty() && removed.peek().is_none() { return; }
+
+    let mut map = state.surfaces.write().unwrap();
+    for entity in removed {
+        map.remove(&entity);
+    }
+    for (entity, surface) in surfaces.iter() {
+        map.insert(entity, *surface);
+    }
+}
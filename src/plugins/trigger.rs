@@ -0,0 +1,287 @@
+//! Trigger volumes, with enter/exit events and a set of overlapping shapes.
+//!
+//! Add [TriggerVolume] component to a shape to turn it into a trigger.
+//! [TriggerEventsPlugin] installs a trigger callback, sends [TriggerEntered]
+//! and [TriggerExited] events, and keeps [OverlappingEntities] of each trigger
+//! up to date.
+//!
+//! If a shape is despawned while inside a trigger, [TriggerExited] is still sent
+//! and the shape is removed from [OverlappingEntities], but event refers
+//! to entities that no longer exist.
+//!
+//! Same overlaps are also triggered as [OnTriggerEnter] and [OnTriggerExit]
+//! observer events, targeting actor and shape entities on both sides.
+//!
+//! Note that PhysX doesn't report overlaps between two triggers.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_mod_physx::prelude::*;
+//! fn print_triggers(mut events: EventReader<TriggerEntered>) {
+//!     for event in events.read() {
+//!         println!("{:?} entered {:?}", event.other_actor, event.trigger);
+//!     }
+//! }
+//! ```
+use std::collections::HashMap;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use bevy::ecs::entity::EntityHashMap;
+use bevy::prelude::*;
+use physx::traits::Class;
+use physx_sys::{
+    PxPairFlag,
+    PxShapeFlag,
+    PxShape_setFlag_mut,
+    PxShape_setFlags_mut,
+    PxTriggerPair,
+    PxTriggerPairFlags,
+};
+
+use crate::core::scene::BuiltinCallbacks;
+use crate::plugins::collision_events::trigger_observers;
+use crate::prelude::{self as bpx, Scene, *};
+use crate::utils::{get_actor_entity_from_ptr, get_shape_entity_from_ptr};
+
+#[derive(Component, Debug, Default, PartialEq, Eq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default)]
+#[require(OverlappingEntities)]
+/// Turn the shape into a trigger volume.
+///
+/// Trigger shapes don't take part in the simulation, they only report
+/// other shapes entering and leaving them. Removing this component restores
+/// flags from [Shape](bpx::Shape).
+pub struct TriggerVolume;
+
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+/// Shapes currently inside a trigger volume, maintained by [TriggerEventsPlugin].
+pub struct OverlappingEntities {
+    // shape -> actor
+    shapes: EntityHashMap<Entity>,
+}
+
+impl OverlappingEntities {
+    /// Whether the entity (either a shape or an actor) is inside the trigger.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.shapes.contains_key(&entity) || self.shapes.values().any(|actor| *actor == entity)
+    }
+
+    /// Shapes inside the trigger.
+    pub fn shapes(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.shapes.keys().copied()
+    }
+
+    /// Actors of the shapes inside the trigger (may repeat, if an actor
+    /// has multiple shapes inside).
+    pub fn actors(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.shapes.values().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+}
+
+/// A shape entered a trigger volume.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerEntered {
+    /// Trigger shape.
+    pub trigger: Entity,
+    /// Actor of the trigger shape.
+    pub trigger_actor: Entity,
+    /// Shape that entered the trigger.
+    pub other: Entity,
+    /// Actor of the shape that entered the trigger.
+    pub other_actor: Entity,
+}
+
+/// A shape left a trigger volume (or one of them was removed).
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerExited {
+    /// Trigger shape.
+    pub trigger: Entity,
+    /// Actor of the trigger shape.
+    pub trigger_actor: Entity,
+    /// Shape that left the trigger.
+    pub other: Entity,
+    /// Actor of the shape that left the trigger.
+    pub other_actor: Entity,
+}
+
+/// Observer event, triggered when a shape enters a trigger, both on the trigger
+/// (shape and actor) and on the shape that entered (and its actor).
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OnTriggerEnter {
+    /// Own shape (either trigger or the shape that entered it).
+    pub shape: Entity,
+    pub other_actor: Entity,
+    pub other_shape: Entity,
+}
+
+/// Observer event, triggered when a shape leaves a trigger, both on the trigger
+/// (shape and actor) and on the shape that left (and its actor).
+///
+/// Not triggered on entities that were despawned.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OnTriggerExit {
+    /// Own shape (either trigger or the shape that left it).
+    pub shape: Entity,
+    pub other_actor: Entity,
+    pub other_shape: Entity,
+}
+
+enum TriggerReport {
+    Entered(TriggerEntered),
+    Exited(TriggerExited),
+}
+
+#[derive(Resource, Deref, DerefMut)]
+struct TriggerReceiver(Mutex<Receiver<TriggerReport>>);
+
+// pairs of overlapping shapes (by shape pointers), shared between the callback
+// and the system that removes triggers
+type OverlappingPairs = Arc<Mutex<HashMap<(usize, usize), TriggerEntered>>>;
+
+#[derive(Resource, Deref)]
+struct ReporterOverlappingPairs(OverlappingPairs);
+
+pub struct TriggerEventsPlugin;
+
+impl Plugin for TriggerEventsPlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = channel();
+
+        let overlapping = OverlappingPairs::default();
+        let mut reporter = TriggerReporter {
+            overlapping: overlapping.clone(),
+            sender,
+        };
+
+        app.register_type::<TriggerVolume>();
+        app.add_event::<TriggerEntered>();
+        app.add_event::<TriggerExited>();
+        app.insert_resource(TriggerReceiver(Mutex::new(receiver)));
+        app.insert_resource(ReporterOverlappingPairs(overlapping));
+
+        app.add_systems(PhysicsSchedule, trigger_volume_sync.in_set(PhysicsSet::Sync));
+        // overlapping sets are updated in the same system that sends events,
+        // so both are consistent with each other right after simulation
+        app.add_systems(PhysicsSchedule, trigger_events.in_set(PhysicsSet::Create));
+
+        let mut builtin = app.world_mut().get_resource_or_init::<BuiltinCallbacks>();
+        builtin.on_trigger.push(Box::new(move |pairs| reporter.report(pairs)));
+    }
+}
+
+fn trigger_volume_sync(
+    mut scene: ResMut<Scene>,
+    reporter_overlapping: Res<ReporterOverlappingPairs>,
+    mut triggers: Query<
+        (Option<&mut ShapeHandle>, Ref<TriggerVolume>),
+        Or<(Added<ShapeHandle>, Added<TriggerVolume>)>,
+    >,
+    mut removed: RemovedComponents<TriggerVolume>,
+    mut shapes: Query<(&mut ShapeHandle, &bpx::Shape, Option<&mut OverlappingEntities>), Without<TriggerVolume>>,
+) {
+    for (handle, trigger) in triggers.iter_mut() {
+        if let Some(mut handle) = handle {
+            let mut handle = handle.get_mut(&mut scene);
+
+            // physx doesn't allow simulation and trigger flags to be set at the same time
+            unsafe {
+                PxShape_setFlag_mut(handle.as_mut_ptr(), PxShapeFlag::SimulationShape, false);
+                PxShape_setFlag_mut(handle.as_mut_ptr(), PxShapeFlag::TriggerShape, true);
+            }
+        } else if !trigger.is_added() {
+            bevy::log::warn!("TriggerVolume component exists, but it's not a shape");
+        }
+    }
+
+    for entity in removed.read() {
+        let Ok((mut handle, shape, overlapping)) = shapes.get_mut(entity) else { continue; };
+        let mut handle = handle.get_mut(&mut scene);
+
+        unsafe { PxShape_setFlags_mut(handle.as_mut_ptr(), shape.flags) };
+
+        // required component stays after trigger is removed, but it's no longer updated
+        if let Some(mut overlapping) = overlapping {
+            overlapping.shapes.clear();
+        }
+
+        // shape is no longer a trigger, so touch lost won't be reported for its pairs
+        reporter_overlapping.lock().unwrap().retain(|_, event| event.trigger != entity);
+    }
+}
+
+fn trigger_events(
+    mut commands: Commands,
+    receiver: Res<TriggerReceiver>,
+    entities: Query<()>,
+    mut overlapping: Query<&mut OverlappingEntities>,
+    mut entered: EventWriter<TriggerEntered>,
+    mut exited: EventWriter<TriggerExited>,
+) {
+    for report in receiver.lock().unwrap().try_iter() {
+        match report {
+            TriggerReport::Entered(event) => {
+                if let Ok(mut overlapping) = overlapping.get_mut(event.trigger) {
+                    overlapping.shapes.insert(event.other, event.other_actor);
+                }
+                let TriggerEntered { trigger, trigger_actor, other, other_actor } = event;
+                trigger_observers(&mut commands, OnTriggerEnter { shape: trigger, other_actor, other_shape: other }, trigger_actor, trigger, &entities);
+                trigger_observers(&mut commands, OnTriggerEnter { shape: other, other_actor: trigger_actor, other_shape: trigger }, other_actor, other, &entities);
+                entered.write(event);
+            }
+            TriggerReport::Exited(event) => {
+                if let Ok(mut overlapping) = overlapping.get_mut(event.trigger) {
+                    overlapping.shapes.remove(&event.other);
+                }
+                let TriggerExited { trigger, trigger_actor, other, other_actor } = event;
+                trigger_observers(&mut commands, OnTriggerExit { shape: trigger, other_actor, other_shape: other }, trigger_actor, trigger, &entities);
+                trigger_observers(&mut commands, OnTriggerExit { shape: other, other_actor: trigger_actor, other_shape: trigger }, other_actor, other, &entities);
+                exited.write(event);
+            }
+        }
+    }
+}
+
+struct TriggerReporter {
+    // pairs of overlapping shapes, so we can report entities for removed shapes,
+    // whose pointers can no longer be dereferenced
+    overlapping: OverlappingPairs,
+    sender: Sender<TriggerReport>,
+}
+
+impl TriggerReporter {
+    fn report(&mut self, pairs: &[PxTriggerPair]) {
+        for pair in pairs {
+            let key = (pair.triggerShape as usize, pair.otherShape as usize);
+
+            if pair.status == PxPairFlag::NotifyTouchFound {
+                if pair.flags.intersects(PxTriggerPairFlags::RemovedShapeTrigger | PxTriggerPairFlags::RemovedShapeOther) {
+                    continue;
+                }
+
+                // SAFETY: actors and shapes are created by this crate, and not removed (checked above)
+                let event = TriggerEntered {
+                    trigger: unsafe { get_shape_entity_from_ptr(pair.triggerShape as *const _) },
+                    trigger_actor: unsafe { get_actor_entity_from_ptr(pair.triggerActor as *const _) },
+                    other: unsafe { get_shape_entity_from_ptr(pair.otherShape as *const _) },
+                    other_actor: unsafe { get_actor_entity_from_ptr(pair.otherActor as *const _) },
+                };
+
+                self.overlapping.lock().unwrap().insert(key, event);
+                self.sender.send(TriggerReport::Entered(event)).unwrap();
+            } else if pair.status == PxPairFlag::NotifyTouchLost {
+                let Some(TriggerEntered { trigger, trigger_actor, other, other_actor }) = self.overlapping.lock().unwrap().remove(&key) else { continue; };
+                self.sender.send(TriggerReport::Exited(TriggerExited { trigger, trigger_actor, other, other_actor })).unwrap();
+            }
+        }
+    }
+}
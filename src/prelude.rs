@@ -20,6 +20,8 @@ pub use crate::core::articulation::{
     ArticulationLinkHandle,
     ArticulationRootHandle,
 };
+pub use crate::core::contact_modify::{ContactModifier, ContactModifyPair, ModifiableContact};
+pub use crate::core::filter::{FilterPair, FilterResult, LostPair, PairStatusChange, SimulationFilter};
 pub use crate::core::foundation::{FoundationDescriptor, Physics};
 pub use crate::core::geometry::Geometry;
 pub use crate::core::material::Material;
@@ -37,19 +39,70 @@ pub use crate::plugins::articulation::{
     ArticulationRoot,
 };
 
+pub use crate::plugins::collision_events::{
+    Collision,
+    CollisionEnded,
+    CollisionEventsPlugin,
+    CollisionPersisted,
+    CollisionStarted,
+    ContactEvents,
+    ContactForceEvent,
+    ContactForceThreshold,
+    ContactPoint,
+    Contacts,
+    OnCollisionEnd,
+    OnCollisionStart,
+};
+pub use crate::plugins::collision_exclusions::{CollisionExclusions, CollisionExclusionsPlugin};
+pub use crate::plugins::collision_layers::{CollisionLayers, CollisionLayersPlugin, PhysicsLayer};
+pub use crate::plugins::constraint_break::ConstraintBroken;
 pub use crate::plugins::damping::Damping;
 #[cfg(feature = "debug-render")]
 pub use crate::plugins::debug_render::DebugRenderSettings;
 pub use crate::plugins::external_force::ExternalForce;
+pub use crate::plugins::joint::{
+    D6Joint,
+    D6JointDrive,
+    D6JointDriveTarget,
+    D6JointDriveVelocity,
+    D6JointDrives,
+    D6JointMotion,
+    DistanceJoint,
+    FixedJoint,
+    Joint,
+    JointHandle,
+    JointLimit,
+    JointLimitCone,
+    PrismaticJoint,
+    RevoluteJoint,
+    SphericalJoint,
+};
 pub use crate::plugins::kinematic::Kinematic;
 pub use crate::plugins::mass_properties::MassProperties;
+pub use crate::plugins::modify_contacts::ModifyContacts;
 pub use crate::plugins::name::NameFormatter;
+pub use crate::plugins::one_way_platform::{OneWayPlatform, OneWayPlatformPlugin};
+#[cfg(feature = "picking")]
+pub use crate::plugins::picking::{PhysicsPickingCamera, PhysicsPickingPlugin, PhysicsPickingSettings};
 pub use crate::plugins::shape_filter_data::ShapeFilterData;
 pub use crate::plugins::shape_offsets::ShapeOffsets;
 pub use crate::plugins::sleep::{SleepControl, Sleeping};
+pub use crate::plugins::surface_velocity::{SurfaceVelocity, SurfaceVelocityPlugin};
+pub use crate::plugins::trigger::{
+    OnTriggerEnter,
+    OnTriggerExit,
+    OverlappingEntities,
+    TriggerEntered,
+    TriggerEventsPlugin,
+    TriggerExited,
+    TriggerVolume,
+};
 pub use crate::plugins::velocity::{MaxVelocity, Velocity};
 pub use crate::plugins::lock_flags::RigidDynamicLockFlags;
 
+pub use crate::utils::bvh::{BvhHit, BvhQueryFilter, PhysicsBvh};
 pub use crate::utils::events::AppExtensions;
-pub use crate::utils::raycast::{RaycastHit, SceneQueryExt};
+pub use crate::utils::geometry_query::{GeometryAssetQuery, GeometryQuery};
+pub use crate::utils::raycast::{OverlapHit, RaycastHit, SceneQueryExt, SweepHit};
+pub use crate::utils::spatial_query::{SceneQueryRequest, SceneQueryResult, SpatialQuery};
 pub use crate::utils::type_bridge::*;
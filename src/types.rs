@@ -2,9 +2,12 @@
 //!
 use std::cell::RefCell;
 
-use bevy::prelude::Entity;
+use bevy::prelude::{Entity, Vec3};
 use physx::prelude::*;
 
+use crate::utils::{get_actor_entity_from_ptr, get_shape_entity_from_ptr};
+use crate::utils::type_bridge::IntoBevyVec3;
+
 pub type PxMaterial = physx::material::PxMaterial<()>;
 pub type PxShape = physx::shape::PxShape<Entity, PxMaterial>;
 pub type PxArticulationLink = physx::articulation_link::PxArticulationLink<Entity, PxShape>;
@@ -199,3 +202,139 @@ impl AdvanceCallback<PxArticulationLink, PxRigidDynamic> for OnAdvance {
         (self.callback.borrow_mut().as_mut().unwrap())(actors, transforms);
     }
 }
+
+/// A single contact point between two shapes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContactPoint {
+    /// Position of the contact point in world space.
+    pub position: Vec3,
+    /// Contact normal, pointing from the second shape to the first one.
+    pub normal: Vec3,
+    /// Impulse applied at the contact point by the solver.
+    pub impulse: Vec3,
+    /// Separation of the shapes at the contact point, negative means penetration.
+    pub separation: f32,
+}
+
+/// Safe view of [PxContactPairHeader](physx_sys::PxContactPairHeader), passed to [OnCollision].
+#[derive(Clone, Copy)]
+pub struct ContactPairHeader<'a> {
+    header: &'a physx_sys::PxContactPairHeader,
+}
+
+impl<'a> ContactPairHeader<'a> {
+    /// Wrap a header passed to [OnCollision].
+    ///
+    /// # Safety
+    /// `header` must be passed by PhysX to a collision callback of a scene created
+    /// by this crate (its actors and pairs are dereferenced), and the view must not
+    /// be kept after the callback returns.
+    pub unsafe fn new(header: &'a physx_sys::PxContactPairHeader) -> Self {
+        Self { header }
+    }
+
+    /// Entities of both actors, or `None` if either of them was removed
+    /// (their pointers can no longer be dereferenced).
+    pub fn actors(&self) -> Option<(Entity, Entity)> {
+        if self.header.flags.intersects(
+            physx_sys::PxContactPairHeaderFlags::RemovedActor0 | physx_sys::PxContactPairHeaderFlags::RemovedActor1
+        ) {
+            return None;
+        }
+
+        // SAFETY: actors are created by this crate, and not removed (checked above)
+        unsafe {
+            Some((
+                get_actor_entity_from_ptr(self.header.actors[0] as *const _),
+                get_actor_entity_from_ptr(self.header.actors[1] as *const _),
+            ))
+        }
+    }
+
+    pub fn flags(&self) -> physx_sys::PxContactPairHeaderFlags {
+        self.header.flags
+    }
+
+    /// Pairs of shapes of these actors.
+    pub fn pairs(&self) -> impl Iterator<Item = ContactPair<'a>> {
+        let pairs = if self.header.pairs.is_null() || self.header.nbPairs == 0 {
+            &[]
+        } else {
+            // SAFETY: physx passes `nbPairs` pairs, valid for the duration of the callback
+            unsafe { std::slice::from_raw_parts(self.header.pairs, self.header.nbPairs as usize) }
+        };
+        // SAFETY: pairs come from the same callback as the header
+        pairs.iter().map(|pair| unsafe { ContactPair::new(pair) })
+    }
+}
+
+/// Safe view of [PxContactPair](physx_sys::PxContactPair), passed to [OnCollision].
+#[derive(Clone, Copy)]
+pub struct ContactPair<'a> {
+    pair: &'a physx_sys::PxContactPair,
+}
+
+impl<'a> ContactPair<'a> {
+    /// Wrap a pair passed to [OnCollision].
+    ///
+    /// # Safety
+    /// `pair` must be passed by PhysX to a collision callback of a scene created
+    /// by this crate (its shapes and contact stream are dereferenced), and the view
+    /// must not be kept after the callback returns.
+    pub unsafe fn new(pair: &'a physx_sys::PxContactPair) -> Self {
+        Self { pair }
+    }
+
+    /// Entities of both shapes, or `None` if either of them was removed
+    /// (their pointers can no longer be dereferenced).
+    pub fn shapes(&self) -> Option<(Entity, Entity)> {
+        if self.pair.flags.intersects(
+            physx_sys::PxContactPairFlags::RemovedShape0 | physx_sys::PxContactPairFlags::RemovedShape1
+        ) {
+            return None;
+        }
+
+        // SAFETY: shapes are created by this crate, and not removed (checked above)
+        unsafe {
+            Some((
+                get_shape_entity_from_ptr(self.pair.shapes[0] as *const _),
+                get_shape_entity_from_ptr(self.pair.shapes[1] as *const _),
+            ))
+        }
+    }
+
+    /// Raw shape pointers, can be used as keys to match events for removed shapes.
+    pub fn shape_ptrs(&self) -> (usize, usize) {
+        (self.pair.shapes[0] as usize, self.pair.shapes[1] as usize)
+    }
+
+    /// Events that triggered this report (touch found, lost, etc.).
+    pub fn events(&self) -> physx_sys::PxPairFlags {
+        self.pair.events
+    }
+
+    pub fn flags(&self) -> physx_sys::PxContactPairFlags {
+        self.pair.flags
+    }
+
+    /// Contact points, only available if contact points were requested for the pair.
+    pub fn contacts(&self) -> impl Iterator<Item = ContactPoint> {
+        let count = self.pair.contactCount as usize;
+        let mut buffer = Vec::<physx_sys::PxContactPairPoint>::with_capacity(count);
+
+        if count > 0 {
+            let extracted = unsafe {
+                physx_sys::PxContactPair_extractContacts(self.pair, buffer.as_mut_ptr(), count as u32)
+            };
+            // SAFETY: physx initialized `extracted` elements, which is never more than buffer size
+            unsafe { buffer.set_len(extracted as usize) };
+        }
+
+        buffer.into_iter().map(|point| ContactPoint {
+            position: point.position.to_bevy(),
+            normal: point.normal.to_bevy(),
+            impulse: point.impulse.to_bevy(),
+            separation: point.separation,
+        })
+    }
+}
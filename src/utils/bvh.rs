@@ -0,0 +1,597 @@
+//! Standalone bounding volume hierarchy for game logic queries.
+//!
+//! [PhysicsBvh] is an AABB tree (PhysX `PxBVH`) built from a set of entities
+//! and their bounds. It is not connected to the physics scene in any way,
+//! so it can be used to index objects that don't have colliders (audio emitters,
+//! AI points of interest, etc.).
+//!
+//! Bounds can be moved with [PhysicsBvh::set_bounds] followed by [PhysicsBvh::refit],
+//! which is cheaper than [PhysicsBvh::rebuild], but the tree quality degrades
+//! over time if objects move far away from their original positions.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy::math::bounding::Aabb3d;
+//! # use bevy_mod_physx::prelude::{self as bpx, *};
+//! fn build_bvh(
+//!     mut commands: Commands,
+//!     mut physics: ResMut<bpx::Physics>,
+//!     query: Query<(Entity, &GlobalTransform), With<Name>>,
+//! ) {
+//!     let items = query.iter().map(|(entity, transform)| {
+//!         (entity, Aabb3d::new(transform.translation(), Vec3::splat(0.5)))
+//!     });
+//!     commands.insert_resource(PhysicsBvh::new(&mut physics, items).unwrap());
+//! }
+//!
+//! fn query_bvh(bvh: Res<PhysicsBvh>) {
+//!     if let Some(hit) = bvh.raycast(Ray3d::new(Vec3::ZERO, Dir3::X), 100., &default()) {
+//!         println!("hit {:?} at {}", hit.entity, hit.position);
+//!     }
+//! }
+//! ```
+use std::collections::hash_map::Entry;
+use std::ffi::c_void;
+use std::mem::MaybeUninit;
+use std::ptr::{null_mut, NonNull};
+
+use bevy::ecs::entity::{EntityHashMap, EntityHashSet};
+use bevy::math::bounding::{Aabb3d, BoundingVolume, RayCast3d};
+use bevy::prelude::*;
+use bevy::render::primitives::Frustum;
+use physx::prelude::*;
+use physx::traits::Class;
+use physx_sys::{
+    phys_PxCreateBVH,
+    PxBVH,
+    PxBVHDesc_new,
+    PxBVH_OverlapCallback,
+    PxBVH_RaycastCallback,
+    PxBVH_cull,
+    PxBVH_getBounds,
+    PxBVH_overlap,
+    PxBVH_partialRefit_mut,
+    PxBVH_raycast,
+    PxBVH_release_mut,
+    PxBVH_sweep,
+    PxBVH_updateBounds_mut,
+    PxBounds3,
+    PxBounds3_new_1,
+    PxGeomSweepHit,
+    PxGeometryQueryFlags,
+    PxGeometryQuery_sweep,
+    PxHitFlags,
+    PxPhysics_getPhysicsInsertionCallback_mut,
+    PxPlane,
+    PxPlane_new_2,
+};
+
+use crate::prelude::{self as bpx, *};
+
+/// Closest hit returned by [PhysicsBvh::raycast] and [PhysicsBvh::sweep].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BvhHit {
+    pub entity: Entity,
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub distance: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BvhBuildError {
+    Failure,
+}
+
+/// Filter for [PhysicsBvh] queries, entities rejected by it are skipped.
+#[derive(Default)]
+pub struct BvhQueryFilter<'a> {
+    predicate: Option<Box<dyn Fn(Entity) -> bool + 'a>>,
+}
+
+impl<'a> BvhQueryFilter<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only hit entities for which `predicate` returns `true`.
+    pub fn with_predicate(predicate: impl Fn(Entity) -> bool + 'a) -> Self {
+        Self { predicate: Some(Box::new(predicate)) }
+    }
+
+    /// Ignore listed entities.
+    pub fn excluding(entities: impl IntoIterator<Item = Entity>) -> Self {
+        let entities = entities.into_iter().collect::<EntityHashSet>();
+        Self::with_predicate(move |entity| !entities.contains(&entity))
+    }
+
+    fn test(&self, entity: Entity) -> bool {
+        self.predicate.as_ref().is_none_or(|predicate| predicate(entity))
+    }
+}
+
+/// AABB tree over arbitrary entities, see [module documentation](self).
+#[derive(Resource, Asset, TypePath)]
+pub struct PhysicsBvh {
+    // null if there are no bounds, physx doesn't create empty trees
+    bvh: Option<NonNull<PxBVH>>,
+    entities: Vec<Entity>,
+    indices: EntityHashMap<u32>,
+}
+
+// SAFETY: PxBVH queries are read-only, and all modifications require &mut self
+unsafe impl Send for PhysicsBvh {}
+unsafe impl Sync for PhysicsBvh {}
+
+impl PhysicsBvh {
+    /// Build a new tree from entities and their bounds.
+    pub fn new(
+        physics: &mut bpx::Physics,
+        items: impl IntoIterator<Item = (Entity, Aabb3d)>,
+    ) -> Result<Self, BvhBuildError> {
+        let mut result = Self {
+            bvh: None,
+            entities: Vec::new(),
+            indices: default(),
+        };
+        result.rebuild(physics, items)?;
+        Ok(result)
+    }
+
+    /// Replace all entities and bounds, building the tree from scratch.
+    ///
+    /// If an entity is listed more than once, its last bounds are used.
+    pub fn rebuild(
+        &mut self,
+        physics: &mut bpx::Physics,
+        items: impl IntoIterator<Item = (Entity, Aabb3d)>,
+    ) -> Result<(), BvhBuildError> {
+        self.release();
+
+        let mut bounds = vec![];
+        for (entity, aabb) in items {
+            match self.indices.entry(entity) {
+                Entry::Occupied(entry) => {
+                    bounds[*entry.get() as usize] = aabb_to_physx(&aabb);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(self.entities.len() as u32);
+                    self.entities.push(entity);
+                    bounds.push(aabb_to_physx(&aabb));
+                }
+            }
+        }
+
+        if bounds.is_empty() { return Ok(()); }
+
+        let mut desc = unsafe { PxBVHDesc_new() };
+        desc.bounds.count = bounds.len() as u32;
+        desc.bounds.stride = std::mem::size_of::<PxBounds3>() as u32;
+        desc.bounds.data = bounds.as_ptr() as *const c_void;
+
+        let bvh = unsafe {
+            let insertion_callback = PxPhysics_getPhysicsInsertionCallback_mut(physics.physics_mut().as_mut_ptr());
+            phys_PxCreateBVH(&desc, insertion_callback)
+        };
+
+        let Some(bvh) = NonNull::new(bvh) else {
+            self.entities.clear();
+            self.indices.clear();
+            return Err(BvhBuildError::Failure);
+        };
+
+        self.bvh = Some(bvh);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.indices.contains_key(&entity)
+    }
+
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    /// Current bounds of an entity (possibly not refitted yet).
+    pub fn bounds(&self, entity: Entity) -> Option<Aabb3d> {
+        let index = *self.indices.get(&entity)?;
+        let bvh = self.bvh?;
+        let bounds = unsafe { &*PxBVH_getBounds(bvh.as_ptr()).add(index as usize) };
+        Some(aabb_from_physx(bounds))
+    }
+
+    /// Move an entity to new bounds.
+    ///
+    /// Tree is not updated until [PhysicsBvh::refit] is called. Returns `false`
+    /// if entity is not in this tree.
+    pub fn set_bounds(&mut self, entity: Entity, aabb: Aabb3d) -> bool {
+        let (Some(&index), Some(bvh)) = (self.indices.get(&entity), self.bvh) else { return false; };
+        unsafe { PxBVH_updateBounds_mut(bvh.as_ptr(), index, &aabb_to_physx(&aabb)) }
+    }
+
+    /// Refit tree nodes for all bounds changed with [PhysicsBvh::set_bounds].
+    pub fn refit(&mut self) {
+        let Some(bvh) = self.bvh else { return; };
+        unsafe { PxBVH_partialRefit_mut(bvh.as_ptr()) };
+    }
+
+    /// Find closest entity whose bounds are hit by a ray.
+    pub fn raycast(&self, ray: Ray3d, max_distance: f32, filter: &BvhQueryFilter) -> Option<BvhHit> {
+        let bvh = self.bvh?;
+        let ray_cast = RayCast3d::from_ray(ray, max_distance);
+        let mut closest = None;
+
+        let mut report = |index: u32, distance: &mut f32| {
+            if !filter.test(self.entities[index as usize]) { return true; }
+
+            let aabb = self.bounds_at(bvh, index);
+            if let Some(hit_distance) = ray_cast.aabb_intersection_at(&aabb) {
+                if hit_distance <= *distance {
+                    *distance = hit_distance;
+                    closest = Some((index, hit_distance));
+                }
+            }
+            true
+        };
+
+        let mut callback = RaycastCallback::new(&mut report);
+        unsafe {
+            PxBVH_raycast(
+                bvh.as_ptr(),
+                &ray.origin.to_physx_sys(),
+                &ray.direction.to_physx_sys(),
+                max_distance,
+                callback.as_mut_ptr(),
+                PxGeometryQueryFlags::Default,
+            );
+        }
+
+        let (index, distance) = closest?;
+        let position = ray.get_point(distance);
+        Some(BvhHit {
+            entity: self.entities[index as usize],
+            position,
+            normal: aabb_normal(&self.bounds_at(bvh, index), position),
+            distance,
+        })
+    }
+
+    /// Find closest entity whose bounds are hit by a swept geometry.
+    ///
+    /// Swept geometry must be a sphere, capsule, box or convex mesh.
+    pub fn sweep(
+        &self,
+        geometry: &bpx::Geometry,
+        pose: Transform,
+        direction: Dir3,
+        max_distance: f32,
+        filter: &BvhQueryFilter,
+    ) -> Option<BvhHit> {
+        let bvh = self.bvh?;
+        if !geometry.is_convex() { return None; }
+
+        let geometry = geometry.to_raw(&pose);
+        let direction = direction.to_physx_sys();
+        let mut closest = None;
+
+        let mut report = |index: u32, distance: &mut f32| {
+            if !filter.test(self.entities[index as usize]) { return true; }
+
+            let aabb = self.bounds_at(bvh, index);
+            let half_size = aabb.half_size();
+            let bounds_geometry = PxBoxGeometry::new(half_size.x, half_size.y, half_size.z);
+            let bounds_pose = Transform::from_translation(aabb.center().into()).to_physx_sys();
+            let mut sweep_hit = MaybeUninit::<PxGeomSweepHit>::uninit();
+
+            let found = unsafe {
+                PxGeometryQuery_sweep(
+                    &direction,
+                    *distance,
+                    geometry.as_ptr(),
+                    geometry.pose(),
+                    bounds_geometry.as_ptr(),
+                    &bounds_pose,
+                    sweep_hit.as_mut_ptr(),
+                    PxHitFlags::Default,
+                    0.,
+                    PxGeometryQueryFlags::Default,
+                    null_mut(),
+                )
+            };
+
+            if found {
+                // SAFETY: sweep returned true, so we assume buffer is initialized
+                let sweep_hit = unsafe { sweep_hit.assume_init() };
+                *distance = sweep_hit.distance;
+                closest = Some((index, sweep_hit));
+            }
+            true
+        };
+
+        let mut callback = RaycastCallback::new(&mut report);
+        unsafe {
+            PxBVH_sweep(
+                bvh.as_ptr(),
+                geometry.as_ptr(),
+                geometry.pose(),
+                &direction,
+                max_distance,
+                callback.as_mut_ptr(),
+                PxGeometryQueryFlags::Default,
+            );
+        }
+
+        let (index, hit) = closest?;
+        Some(BvhHit {
+            entity: self.entities[index as usize],
+            position: hit.position.to_bevy(),
+            normal: hit.normal.to_bevy(),
+            distance: hit.distance,
+        })
+    }
+
+    /// Find all entities whose bounds overlap a geometry.
+    ///
+    /// Spheres, capsules and boxes are tested exactly, other geometries
+    /// are tested using their bounds.
+    pub fn overlap(&self, geometry: &bpx::Geometry, pose: Transform, filter: &BvhQueryFilter) -> Vec<Entity> {
+        let Some(bvh) = self.bvh else { return vec![]; };
+        let mut result = vec![];
+
+        let mut report = |index: u32| {
+            let entity = self.entities[index as usize];
+            if filter.test(entity) { result.push(entity); }
+            true
+        };
+
+        let mut callback = OverlapCallback::new(&mut report);
+        geometry.with_raw(&pose, |geometry, pose| unsafe {
+            PxBVH_overlap(
+                bvh.as_ptr(),
+                geometry,
+                pose,
+                callback.as_mut_ptr(),
+                PxGeometryQueryFlags::Default,
+            );
+        });
+
+        result
+    }
+
+    /// Find all entities whose bounds are (possibly partially) inside a frustum.
+    pub fn cull(&self, frustum: &Frustum) -> Vec<Entity> {
+        let Some(bvh) = self.bvh else { return vec![]; };
+        let mut result = vec![];
+
+        // bevy half-spaces point inside, physx planes point outside
+        let planes: Vec<PxPlane> = frustum.half_spaces.iter().map(|half_space| {
+            let plane = -half_space.normal_d();
+            unsafe { PxPlane_new_2(plane.x, plane.y, plane.z, plane.w) }
+        }).collect();
+
+        let mut report = |index: u32| {
+            result.push(self.entities[index as usize]);
+            true
+        };
+
+        let mut callback = OverlapCallback::new(&mut report);
+        unsafe {
+            PxBVH_cull(
+                bvh.as_ptr(),
+                planes.len() as u32,
+                planes.as_ptr(),
+                callback.as_mut_ptr(),
+                PxGeometryQueryFlags::Default,
+            );
+        }
+
+        result
+    }
+
+    fn bounds_at(&self, bvh: NonNull<PxBVH>, index: u32) -> Aabb3d {
+        aabb_from_physx(unsafe { &*PxBVH_getBounds(bvh.as_ptr()).add(index as usize) })
+    }
+
+    fn release(&mut self) {
+        if let Some(bvh) = self.bvh.take() {
+            unsafe { PxBVH_release_mut(bvh.as_ptr()) };
+        }
+        self.entities.clear();
+        self.indices.clear();
+    }
+}
+
+impl Drop for PhysicsBvh {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+fn aabb_to_physx(aabb: &Aabb3d) -> PxBounds3 {
+    let min = Vec3::from(aabb.min).to_physx_sys();
+    let max = Vec3::from(aabb.max).to_physx_sys();
+    unsafe { PxBounds3_new_1(&min, &max) }
+}
+
+fn aabb_from_physx(bounds: &PxBounds3) -> Aabb3d {
+    Aabb3d {
+        min: bounds.minimum.to_bevy().into(),
+        max: bounds.maximum.to_bevy().into(),
+    }
+}
+
+// normal of the aabb face closest to a point on its surface
+fn aabb_normal(aabb: &Aabb3d, point: Vec3) -> Vec3 {
+    let local = (point - Vec3::from(aabb.center())) / Vec3::from(aabb.half_size()).max(Vec3::splat(f32::EPSILON));
+    let abs = local.abs();
+
+    if abs.x >= abs.y && abs.x >= abs.z {
+        Vec3::X * local.x.signum()
+    } else if abs.y >= abs.z {
+        Vec3::Y * local.y.signum()
+    } else {
+        Vec3::Z * local.z.signum()
+    }
+}
+
+// PxBVH::RaycastCallback and PxBVH::OverlapCallback are pure virtual C++ classes
+// not exposed through physx-sys C api, so we construct their vtables manually.
+//
+// Both start with a virtual destructor, which takes two slots in Itanium ABI
+// and one slot in MSVC ABI. Destructors are never called by physx.
+unsafe extern "C" fn noop_destructor(_this: *mut c_void) {}
+
+#[repr(C)]
+struct RaycastCallbackVtable {
+    #[cfg(not(target_env = "msvc"))]
+    complete_destructor: unsafe extern "C" fn(*mut c_void),
+    deleting_destructor: unsafe extern "C" fn(*mut c_void),
+    report_hit: unsafe extern "C" fn(*mut RaycastCallback, u32, *mut f32) -> bool,
+}
+
+#[repr(C)]
+struct RaycastCallback<'a> {
+    vtable: *const RaycastCallbackVtable,
+    report: &'a mut dyn FnMut(u32, &mut f32) -> bool,
+}
+
+impl<'a> RaycastCallback<'a> {
+    const VTABLE: RaycastCallbackVtable = RaycastCallbackVtable {
+        #[cfg(not(target_env = "msvc"))]
+        complete_destructor: noop_destructor,
+        deleting_destructor: noop_destructor,
+        report_hit: Self::report_hit,
+    };
+
+    fn new(report: &'a mut dyn FnMut(u32, &mut f32) -> bool) -> Self {
+        Self { vtable: &Self::VTABLE, report }
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut PxBVH_RaycastCallback {
+        self as *mut Self as *mut PxBVH_RaycastCallback
+    }
+
+    unsafe extern "C" fn report_hit(this: *mut RaycastCallback, index: u32, distance: *mut f32) -> bool {
+        // SAFETY: physx calls this with the pointer we passed in, during the query
+        let this = unsafe { &mut *this };
+        (this.report)(index, unsafe { &mut *distance })
+    }
+}
+
+#[repr(C)]
+struct OverlapCallbackVtable {
+    #[cfg(not(target_env = "msvc"))]
+    complete_destructor: unsafe extern "C" fn(*mut c_void),
+    deleting_destructor: unsafe extern "C" fn(*mut c_void),
+    report_hit: unsafe extern "C" fn(*mut OverlapCallback, u32) -> bool,
+}
+
+#[repr(C)]
+struct OverlapCallback<'a> {
+    vtable: *const OverlapCallbackVtable,
+    report: &'a mut dyn FnMut(u32) -> bool,
+}
+
+impl<'a> OverlapCallback<'a> {
+    const VTABLE: OverlapCallbackVtable = OverlapCallbackVtable {
+        #[cfg(not(target_env = "msvc"))]
+        complete_destructor: noop_destructor,
+        deleting_destructor: noop_destructor,
+        report_hit: Self::report_hit,
+    };
+
+    fn new(report: &'a mut dyn FnMut(u32) -> bool) -> Self {
+        Self { vtable: &Self::VTABLE, report }
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut PxBVH_OverlapCallback {
+        self as *mut Self as *mut PxBVH_OverlapCallback
+    }
+
+    unsafe extern "C" fn report_hit(this: *mut OverlapCallback, index: u32) -> bool {
+        // SAFETY: physx calls this with the pointer we passed in, during the query
+        let this = unsafe { &mut *this };
+        (this.report)(index)
+    }
+}
+
+// checks that vtables above match C++ ABI of the target (PhysX would call
+// a destructor slot instead of reportHit otherwise)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::foundation::test_physics;
+
+    fn test_items() -> [(Entity, Aabb3d); 2] {
+        [
+            (Entity::from_raw(1), Aabb3d::new(Vec3::new(5., 0., 0.), Vec3::splat(1.))),
+            (Entity::from_raw(2), Aabb3d::new(Vec3::new(10., 0., 0.), Vec3::splat(1.))),
+        ]
+    }
+
+    #[test]
+    fn raycast_callback() {
+        let mut physics = test_physics();
+        let [ (a, _), (b, _) ] = test_items();
+        let bvh = PhysicsBvh::new(&mut physics, test_items()).unwrap();
+        let ray = Ray3d::new(Vec3::ZERO, Dir3::X);
+
+        let hit = bvh.raycast(ray, 100., &default()).unwrap();
+        assert_eq!(hit.entity, a);
+        assert!((hit.distance - 4.).abs() < 1e-4);
+        assert_eq!(hit.normal, Vec3::NEG_X);
+
+        let hit = bvh.raycast(ray, 100., &BvhQueryFilter::excluding([ a ])).unwrap();
+        assert_eq!(hit.entity, b);
+
+        assert!(bvh.raycast(ray, 3., &default()).is_none());
+    }
+
+    #[test]
+    fn sweep_callback() {
+        let mut physics = test_physics();
+        let [ (a, _), (b, _) ] = test_items();
+        let bvh = PhysicsBvh::new(&mut physics, test_items()).unwrap();
+        let sphere = bpx::Geometry::from(Sphere::new(0.5));
+
+        let hit = bvh.sweep(&sphere, Transform::IDENTITY, Dir3::X, 100., &default()).unwrap();
+        assert_eq!(hit.entity, a);
+        assert!((hit.distance - 3.5).abs() < 1e-3);
+
+        let hit = bvh.sweep(&sphere, Transform::IDENTITY, Dir3::X, 100., &BvhQueryFilter::excluding([ a ])).unwrap();
+        assert_eq!(hit.entity, b);
+    }
+
+    #[test]
+    fn overlap_callback() {
+        let mut physics = test_physics();
+        let [ (a, _), (b, _) ] = test_items();
+        let bvh = PhysicsBvh::new(&mut physics, test_items()).unwrap();
+        let sphere = bpx::Geometry::from(Sphere::new(1.));
+
+        assert_eq!(bvh.overlap(&sphere, Transform::from_xyz(5.5, 0., 0.), &default()), vec![ a ]);
+        assert_eq!(bvh.overlap(&sphere, Transform::from_xyz(7.5, 0., 0.), &default()), vec![]);
+
+        let mut both = bvh.overlap(&Cuboid::new(20., 1., 1.).into(), Transform::IDENTITY, &default());
+        both.sort();
+        assert_eq!(both, vec![ a, b ]);
+    }
+
+    #[test]
+    fn rebuild_dedupes_entities() {
+        let mut physics = test_physics();
+        let [ (a, first), (_, second) ] = test_items();
+        let bvh = PhysicsBvh::new(&mut physics, [ (a, first), (a, second) ]).unwrap();
+
+        assert_eq!(bvh.len(), 1);
+        assert_eq!(bvh.entities(), &[ a ]);
+        assert_eq!(bvh.bounds(a), Some(second));
+    }
+}
@@ -56,7 +56,7 @@ impl AppExtensions for App {
         self.add_event::<T>();
         self.add_systems(PhysicsSchedule, (
             channel_to_event::<T>,
-        )/*.after(event_update_system::<T>)*/.before(PhysicsSet::Create));
+        )/*.after(event_update_system::<T>)*/.after(PhysicsSet::Simulate).before(PhysicsSet::Create));
 
         self.insert_resource(ChannelReceiver(Mutex::new(receiver)));
         self
@@ -71,7 +71,7 @@ impl AppExtensions for App {
         self.add_physics_event::<T>();
         self.add_systems(PhysicsSchedule, (
             channel_to_event::<T>,
-        )/*.after(event_update_system::<T>)*/.before(PhysicsSet::Create));
+        )/*.after(event_update_system::<T>)*/.after(PhysicsSet::Simulate).before(PhysicsSet::Create));
 
         self.insert_resource(ChannelReceiver(Mutex::new(receiver)));
         self
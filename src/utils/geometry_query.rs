@@ -0,0 +1,499 @@
+//! Queries between individual geometries, without a scene.
+//!
+//! These are thin wrappers over PhysX `PxGeometryQuery` and `PxGjkQuery`. Every
+//! geometry is passed together with a [Transform] describing its pose in the
+//! world (scale is ignored, use [Geometry::with_scale] instead).
+//!
+//! To run queries against geometry assets by their handles (e.g. the collider of
+//! a specific entity), use [GeometryAssetQuery] system parameter.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_mod_physx::prelude::*;
+//! # use bevy_mod_physx::utils::geometry_query::GeometryQuery;
+//! fn is_inside(volume: &Geometry, volume_pose: &Transform, player: Vec3) -> bool {
+//!     GeometryQuery::contains_point(player, volume, volume_pose)
+//! }
+//! ```
+use std::mem::MaybeUninit;
+use std::ptr::null_mut;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use physx_sys::{
+    PxGeomRaycastHit,
+    PxGeomSweepHit,
+    PxGeometryQueryFlags,
+    PxGeometryQuery_computePenetration,
+    PxGeometryQuery_overlap,
+    PxGeometryQuery_pointDistance,
+    PxGeometryQuery_raycast,
+    PxGeometryQuery_sweep,
+    PxGjkQueryExt_ConvexGeomSupport,
+    PxGjkQueryExt_ConvexGeomSupport_delete,
+    PxGjkQueryExt_ConvexGeomSupport_new_alloc_1,
+    PxGjkQuery_Support,
+    PxGjkQuery_proximityInfo,
+    PxHitFlags,
+    PxVec3,
+};
+
+use crate::prelude::*;
+
+/// Result of [GeometryQuery::point_distance].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointDistance {
+    /// Distance between the point and the geometry, zero if the point is inside
+    /// (triangle meshes have no inside, so it's the distance to their surface).
+    pub distance: f32,
+    /// Closest point on the geometry, or the point itself if it's inside.
+    pub closest_point: Vec3,
+}
+
+/// Result of [GeometryQuery::penetration].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Penetration {
+    /// Depenetration direction (unit vector), to move the first geometry
+    /// out of the second one.
+    pub direction: Vec3,
+    /// Penetration depth, translate the first geometry by `direction * depth`
+    /// to separate them.
+    pub depth: f32,
+}
+
+/// Result of [GeometryQuery::distance].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeometryDistance {
+    /// Distance between the geometries, negative if they are penetrating.
+    pub distance: f32,
+    /// Closest point on the first geometry.
+    pub point0: Vec3,
+    /// Closest point on the second geometry.
+    pub point1: Vec3,
+    /// Separating axis, pointing from the second geometry to the first one.
+    pub normal: Vec3,
+}
+
+/// Result of [GeometryQuery::raycast] and [GeometryQuery::sweep].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeometryHit {
+    pub face_index: u32,
+    pub flags: PxHitFlags,
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub distance: f32,
+}
+
+/// Standalone geometry queries, see [module documentation](self).
+pub struct GeometryQuery;
+
+impl GeometryQuery {
+    /// Compute distance between a point and a geometry.
+    ///
+    /// Supported geometries: sphere, capsule, box, convex mesh, triangle mesh.
+    /// Returns `None` for unsupported geometries.
+    pub fn point_distance(point: Vec3, geometry: &Geometry, pose: &Transform) -> Option<PointDistance> {
+        let mut closest_point = PxVec3 { x: 0., y: 0., z: 0. };
+
+        let sq_distance = geometry.with_raw(pose, |geometry, pose| unsafe {
+            PxGeometryQuery_pointDistance(
+                &point.to_physx_sys(),
+                geometry,
+                pose,
+                &mut closest_point,
+                null_mut(),
+                PxGeometryQueryFlags::Default,
+            )
+        });
+
+        // physx returns -1 on error (e.g. unsupported geometry)
+        if sq_distance < 0. { return None; }
+
+        Some(PointDistance {
+            distance: sq_distance.sqrt(),
+            closest_point: if sq_distance > 0. { closest_point.to_bevy() } else { point },
+        })
+    }
+
+    /// Check whether a point is inside of a geometry (or on its surface).
+    ///
+    /// Supported geometries: sphere, capsule, box, convex mesh.
+    /// Returns `false` for other geometries, including triangle meshes, even closed ones.
+    pub fn contains_point(point: Vec3, geometry: &Geometry, pose: &Transform) -> bool {
+        // distance to triangle mesh is measured to its surface
+        if !geometry.is_convex() { return false; }
+
+        Self::point_distance(point, geometry, pose)
+            .is_some_and(|result| result.distance == 0.)
+    }
+
+    /// Find the closest point on a geometry.
+    ///
+    /// Returns `None` for unsupported geometries, see [point_distance](Self::point_distance).
+    pub fn closest_point(point: Vec3, geometry: &Geometry, pose: &Transform) -> Option<Vec3> {
+        Self::point_distance(point, geometry, pose).map(|result| result.closest_point)
+    }
+
+    /// Raycast against a single geometry, returning the closest hit.
+    pub fn raycast(ray: Ray3d, max_distance: f32, geometry: &Geometry, pose: &Transform) -> Option<GeometryHit> {
+        let mut raycast_hit = MaybeUninit::<PxGeomRaycastHit>::uninit();
+
+        let count = geometry.with_raw(pose, |geometry, pose| unsafe {
+            PxGeometryQuery_raycast(
+                &ray.origin.to_physx_sys(),
+                &ray.direction.to_physx_sys(),
+                geometry,
+                pose,
+                max_distance,
+                PxHitFlags::Default,
+                1,
+                raycast_hit.as_mut_ptr(),
+                std::mem::size_of::<PxGeomRaycastHit>() as u32,
+                PxGeometryQueryFlags::Default,
+                null_mut(),
+            )
+        });
+
+        if count == 0 { return None; }
+
+        // SAFETY: raycast returned a hit, so we assume buffer is initialized
+        let raycast_hit = unsafe { raycast_hit.assume_init() };
+
+        Some(GeometryHit {
+            face_index: raycast_hit.faceIndex,
+            flags: raycast_hit.flags,
+            position: raycast_hit.position.to_bevy(),
+            normal: raycast_hit.normal.to_bevy(),
+            distance: raycast_hit.distance,
+        })
+    }
+
+    /// Sweep the first geometry along `direction` against the second one.
+    ///
+    /// Swept geometry must be a sphere, capsule, box or convex mesh.
+    pub fn sweep(
+        geometry0: &Geometry,
+        pose0: &Transform,
+        direction: Dir3,
+        max_distance: f32,
+        geometry1: &Geometry,
+        pose1: &Transform,
+    ) -> Option<GeometryHit> {
+        if !geometry0.is_convex() { return None; }
+
+        let mut sweep_hit = MaybeUninit::<PxGeomSweepHit>::uninit();
+
+        // not nested `with_raw`, so both geometries can share a mesh
+        let raw0 = geometry0.to_raw(pose0);
+        let raw1 = geometry1.to_raw(pose1);
+
+        let found = unsafe {
+            PxGeometryQuery_sweep(
+                &direction.to_physx_sys(),
+                max_distance,
+                raw0.as_ptr(),
+                raw0.pose(),
+                raw1.as_ptr(),
+                raw1.pose(),
+                sweep_hit.as_mut_ptr(),
+                PxHitFlags::Default,
+                0.,
+                PxGeometryQueryFlags::Default,
+                null_mut(),
+            )
+        };
+
+        if !found { return None; }
+
+        // SAFETY: sweep returned true, so we assume buffer is initialized
+        let sweep_hit = unsafe { sweep_hit.assume_init() };
+
+        Some(GeometryHit {
+            face_index: sweep_hit.faceIndex,
+            flags: sweep_hit.flags,
+            position: sweep_hit.position.to_bevy(),
+            normal: sweep_hit.normal.to_bevy(),
+            distance: sweep_hit.distance,
+        })
+    }
+
+    /// Check whether two geometries overlap.
+    pub fn overlap(
+        geometry0: &Geometry,
+        pose0: &Transform,
+        geometry1: &Geometry,
+        pose1: &Transform,
+    ) -> bool {
+        let raw0 = geometry0.to_raw(pose0);
+        let raw1 = geometry1.to_raw(pose1);
+
+        unsafe {
+            PxGeometryQuery_overlap(
+                raw0.as_ptr(),
+                raw0.pose(),
+                raw1.as_ptr(),
+                raw1.pose(),
+                PxGeometryQueryFlags::Default,
+                null_mut(),
+            )
+        }
+    }
+
+    /// Compute minimum translational distance (MTD) between two overlapping geometries.
+    ///
+    /// Returns `None` if geometries don't overlap.
+    pub fn penetration(
+        geometry0: &Geometry,
+        pose0: &Transform,
+        geometry1: &Geometry,
+        pose1: &Transform,
+    ) -> Option<Penetration> {
+        let mut direction = PxVec3 { x: 0., y: 0., z: 0. };
+        let mut depth = 0.;
+
+        let raw0 = geometry0.to_raw(pose0);
+        let raw1 = geometry1.to_raw(pose1);
+
+        let overlap = unsafe {
+            PxGeometryQuery_computePenetration(
+                &mut direction,
+                &mut depth,
+                raw0.as_ptr(),
+                raw0.pose(),
+                raw1.as_ptr(),
+                raw1.pose(),
+                PxGeometryQueryFlags::Default,
+            )
+        };
+
+        overlap.then(|| Penetration { direction: direction.to_bevy(), depth })
+    }
+
+    /// Compute distance and closest points between two convex geometries.
+    ///
+    /// Supported geometries: sphere, capsule, box, convex mesh.
+    /// Returns `None` for other geometries.
+    pub fn distance(
+        geometry0: &Geometry,
+        pose0: &Transform,
+        geometry1: &Geometry,
+        pose1: &Transform,
+    ) -> Option<GeometryDistance> {
+        if !geometry0.is_convex() || !geometry1.is_convex() { return None; }
+
+        let mut point0 = PxVec3 { x: 0., y: 0., z: 0. };
+        let mut point1 = PxVec3 { x: 0., y: 0., z: 0. };
+        let mut normal = PxVec3 { x: 0., y: 0., z: 0. };
+        let mut distance = 0.;
+
+        let raw0 = geometry0.to_raw(pose0);
+        let raw1 = geometry1.to_raw(pose1);
+
+        let found = unsafe {
+            let support0 = PxGjkQueryExt_ConvexGeomSupport_new_alloc_1(raw0.as_ptr(), 0.);
+            let support1 = PxGjkQueryExt_ConvexGeomSupport_new_alloc_1(raw1.as_ptr(), 0.);
+
+            let found = PxGjkQuery_proximityInfo(
+                support0 as *const PxGjkQueryExt_ConvexGeomSupport as *const PxGjkQuery_Support,
+                support1 as *const PxGjkQueryExt_ConvexGeomSupport as *const PxGjkQuery_Support,
+                raw0.pose(),
+                raw1.pose(),
+                f32::MAX,
+                // tolerance length, same as default PxTolerancesScale::length
+                1.,
+                &mut point0,
+                &mut point1,
+                &mut normal,
+                &mut distance,
+            );
+
+            PxGjkQueryExt_ConvexGeomSupport_delete(support0);
+            PxGjkQueryExt_ConvexGeomSupport_delete(support1);
+            found
+        };
+
+        found.then(|| GeometryDistance {
+            distance,
+            point0: point0.to_bevy(),
+            point1: point1.to_bevy(),
+            normal: normal.to_bevy(),
+        })
+    }
+}
+
+/// System parameter that runs [GeometryQuery] on geometries referenced by handles.
+///
+/// All methods return `None` (or `false`) if geometry asset is not found.
+#[derive(SystemParam)]
+pub struct GeometryAssetQuery<'w> {
+    geometries: Res<'w, Assets<Geometry>>,
+}
+
+impl GeometryAssetQuery<'_> {
+    pub fn get(&self, geometry: &Handle<Geometry>) -> Option<&Geometry> {
+        self.geometries.get(geometry)
+    }
+
+    /// See [GeometryQuery::raycast].
+    pub fn raycast(
+        &self,
+        ray: Ray3d,
+        max_distance: f32,
+        geometry: &Handle<Geometry>,
+        pose: &Transform,
+    ) -> Option<GeometryHit> {
+        GeometryQuery::raycast(ray, max_distance, self.get(geometry)?, pose)
+    }
+
+    /// See [GeometryQuery::sweep].
+    pub fn sweep(
+        &self,
+        geometry0: &Handle<Geometry>,
+        pose0: &Transform,
+        direction: Dir3,
+        max_distance: f32,
+        geometry1: &Handle<Geometry>,
+        pose1: &Transform,
+    ) -> Option<GeometryHit> {
+        GeometryQuery::sweep(self.get(geometry0)?, pose0, direction, max_distance, self.get(geometry1)?, pose1)
+    }
+
+    /// See [GeometryQuery::overlap].
+    pub fn overlap(
+        &self,
+        geometry0: &Handle<Geometry>,
+        pose0: &Transform,
+        geometry1: &Handle<Geometry>,
+        pose1: &Transform,
+    ) -> bool {
+        let (Some(geometry0), Some(geometry1)) = (self.get(geometry0), self.get(geometry1)) else { return false; };
+        GeometryQuery::overlap(geometry0, pose0, geometry1, pose1)
+    }
+
+    /// See [GeometryQuery::point_distance].
+    pub fn point_distance(&self, point: Vec3, geometry: &Handle<Geometry>, pose: &Transform) -> Option<PointDistance> {
+        GeometryQuery::point_distance(point, self.get(geometry)?, pose)
+    }
+
+    /// See [GeometryQuery::penetration].
+    pub fn penetration(
+        &self,
+        geometry0: &Handle<Geometry>,
+        pose0: &Transform,
+        geometry1: &Handle<Geometry>,
+        pose1: &Transform,
+    ) -> Option<Penetration> {
+        GeometryQuery::penetration(self.get(geometry0)?, pose0, self.get(geometry1)?, pose1)
+    }
+
+    /// See [GeometryQuery::distance].
+    pub fn distance(
+        &self,
+        geometry0: &Handle<Geometry>,
+        pose0: &Transform,
+        geometry1: &Handle<Geometry>,
+        pose1: &Transform,
+    ) -> Option<GeometryDistance> {
+        GeometryQuery::distance(self.get(geometry0)?, pose0, self.get(geometry1)?, pose1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::foundation::test_physics;
+
+    fn assert_near(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-3, "{a} != {b}");
+    }
+
+    #[test]
+    fn point_distance_to_sphere() {
+        let _physics = test_physics();
+        let sphere = Geometry::from(Sphere::new(1.));
+        let pose = Transform::from_xyz(1., 0., 0.);
+
+        let result = GeometryQuery::point_distance(Vec3::new(4., 0., 0.), &sphere, &pose).unwrap();
+        assert_near(result.distance, 2.);
+        assert!(result.closest_point.distance(Vec3::new(2., 0., 0.)) < 1e-3);
+
+        assert!(GeometryQuery::contains_point(Vec3::new(1.5, 0., 0.), &sphere, &pose));
+        assert!(!GeometryQuery::contains_point(Vec3::new(-0.5, 0., 0.), &sphere, &pose));
+    }
+
+    #[test]
+    fn contains_point_in_rotated_cuboid() {
+        let _physics = test_physics();
+        let cuboid = Geometry::from(Cuboid::new(4., 1., 1.));
+        let pose = Transform::from_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2));
+
+        assert!(GeometryQuery::contains_point(Vec3::new(0., 1.5, 0.), &cuboid, &pose));
+        assert!(!GeometryQuery::contains_point(Vec3::new(1.5, 0., 0.), &cuboid, &pose));
+    }
+
+    #[test]
+    fn penetration_of_spheres() {
+        let _physics = test_physics();
+        let sphere = Geometry::from(Sphere::new(1.));
+
+        let penetration = GeometryQuery::penetration(&sphere, &Transform::IDENTITY, &sphere, &Transform::from_xyz(1.5, 0., 0.)).unwrap();
+        assert_near(penetration.depth, 0.5);
+        // direction pushes the first geometry out of the second one
+        assert!(penetration.direction.distance(Vec3::NEG_X) < 1e-3);
+
+        assert!(GeometryQuery::penetration(&sphere, &Transform::IDENTITY, &sphere, &Transform::from_xyz(3., 0., 0.)).is_none());
+    }
+
+    #[test]
+    fn raycast_sphere() {
+        let _physics = test_physics();
+        let sphere = Geometry::from(Sphere::new(1.));
+        let pose = Transform::from_xyz(5., 0., 0.);
+        let ray = Ray3d::new(Vec3::ZERO, Dir3::X);
+
+        let hit = GeometryQuery::raycast(ray, 100., &sphere, &pose).unwrap();
+        assert_near(hit.distance, 4.);
+        assert!(hit.normal.distance(Vec3::NEG_X) < 1e-3);
+
+        assert!(GeometryQuery::raycast(ray, 3., &sphere, &pose).is_none());
+        assert!(GeometryQuery::raycast(Ray3d::new(Vec3::ZERO, Dir3::Y), 100., &sphere, &pose).is_none());
+    }
+
+    #[test]
+    fn sweep_sphere_against_cuboid() {
+        let _physics = test_physics();
+        let sphere = Geometry::from(Sphere::new(0.5));
+        let cuboid = Geometry::from(Cuboid::new(2., 2., 2.));
+        let pose = Transform::from_xyz(5., 0., 0.);
+
+        let hit = GeometryQuery::sweep(&sphere, &Transform::IDENTITY, Dir3::X, 100., &cuboid, &pose).unwrap();
+        assert_near(hit.distance, 3.5);
+
+        assert!(GeometryQuery::sweep(&sphere, &Transform::IDENTITY, Dir3::NEG_X, 100., &cuboid, &pose).is_none());
+    }
+
+    #[test]
+    fn overlap_of_spheres() {
+        let _physics = test_physics();
+        let sphere = Geometry::from(Sphere::new(1.));
+
+        assert!(GeometryQuery::overlap(&sphere, &Transform::IDENTITY, &sphere, &Transform::from_xyz(1.5, 0., 0.)));
+        assert!(!GeometryQuery::overlap(&sphere, &Transform::IDENTITY, &sphere, &Transform::from_xyz(3., 0., 0.)));
+    }
+
+    #[test]
+    fn distance_between_convex_geometries() {
+        let _physics = test_physics();
+        let sphere = Geometry::from(Sphere::new(1.));
+        let cuboid = Geometry::from(Cuboid::new(2., 2., 2.));
+
+        let result = GeometryQuery::distance(&sphere, &Transform::IDENTITY, &cuboid, &Transform::from_xyz(5., 0., 0.)).unwrap();
+        assert_near(result.distance, 3.);
+        assert!(result.point0.distance(Vec3::new(1., 0., 0.)) < 1e-3);
+        assert!(result.point1.distance(Vec3::new(4., 0., 0.)) < 1e-3);
+
+        // triangle meshes and planes are not supported
+        let plane = Geometry::from(Plane3d::new(Vec3::Y, Vec2::splat(10.)));
+        assert!(GeometryQuery::distance(&sphere, &Transform::IDENTITY, &plane, &Transform::IDENTITY).is_none());
+    }
+}
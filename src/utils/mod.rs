@@ -1,11 +1,16 @@
 //! Various helpers and extension traits.
+pub mod bvh;
 pub mod events;
+pub mod geometry_query;
 pub mod raycast;
+pub mod spatial_query;
 pub mod type_bridge;
 
+use std::ffi::CStr;
+
 use bevy::prelude::*;
 use physx::prelude::*;
-use physx_sys::PxBase_getConcreteType;
+use physx_sys::{PxBase_getConcreteType, PxJoint_getName};
 
 use crate::types::*;
 
@@ -63,3 +68,23 @@ pub unsafe fn get_shape_entity_from_ptr(shape: *const physx_sys::PxShape) -> Ent
     let shape = &*(shape as *const PxShape);
     *shape.get_user_data()
 }
+
+/// Get joint [Entity] from raw [PxJoint](physx_sys::PxJoint) pointer from PhysX.
+///
+/// Returns `None` if joint wasn't created by bevy_mod_physx crate (see [crate::plugins::joint]),
+/// or if its name was changed.
+///
+/// # Safety
+/// User must ensure that pointer is valid.
+pub unsafe fn get_joint_entity_from_ptr(joint: *const physx_sys::PxJoint) -> Option<Entity> {
+    // other joints can store anything in userdata, so ours are tagged with a name
+    // (compared by pointer, it's the same static string for all of them)
+    if unsafe { PxJoint_getName(joint) } != JOINT_NAME.as_ptr() { return None; }
+
+    // joints store entity bits directly in userdata pointer
+    let user_data = unsafe { (*joint).userData };
+    Entity::try_from_bits(user_data as usize as u64).ok()
+}
+
+// name of joints created by this crate, see get_joint_entity_from_ptr
+pub(crate) static JOINT_NAME: &CStr = c"bevy_mod_physx";
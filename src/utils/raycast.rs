@@ -1,25 +1,38 @@
-//! Extension trait for Scene that adds raycasts.
+//! Extension trait for Scene that adds raycasts, sweeps and overlaps.
 use std::ffi::c_void;
+use std::marker::PhantomData;
 use std::mem::MaybeUninit;
 use std::ptr::{drop_in_place, null_mut};
 
+use bevy::ecs::entity::EntityHashSet;
 use bevy::prelude::*;
 use physx::rigid_actor::RigidActor;
 use physx::traits::Class;
 use physx_sys::{
     create_raycast_filter_callback,
     create_raycast_filter_callback_func,
+    PxFilterData,
+    PxFilterData_new_2,
     PxHitFlags,
+    PxOverlapHit,
     PxQueryFilterCallback,
     PxQueryFilterCallback_delete,
     PxQueryFilterData,
     PxQueryFilterData_new,
     PxQueryFlags,
+    PxQueryHitType,
+    PxRigidActor,
+    PxSceneQueryExt_overlapMultiple,
     PxSceneQueryExt_raycastSingle,
+    PxSceneQueryExt_sweepSingle,
+    PxShape,
+    PxShape_getSimulationFilterData,
     RaycastHitCallback,
 };
 
-use crate::prelude::{Scene, *};
+use crate::core::filter::{self, COLLISION_LAYERS};
+use crate::prelude::{self as bpx, Scene, *};
+use crate::types::PxScene;
 use crate::utils::{get_actor_entity_from_ptr, get_shape_entity_from_ptr};
 
 #[derive(Debug)]
@@ -33,11 +46,71 @@ pub struct RaycastHit {
     pub distance: f32,
 }
 
+#[derive(Debug)]
+pub struct SweepHit {
+    pub actor: Entity,
+    pub shape: Entity,
+    pub face_index: u32,
+    pub flags: PxHitFlags,
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub distance: f32,
+}
+
+#[derive(Debug)]
+pub struct OverlapHit {
+    pub actor: Entity,
+    pub shape: Entity,
+    pub face_index: u32,
+}
+
+/// Result of a query filter, tells PhysX what to do with a shape.
+///
+///  - `None` - shape is ignored;
+///  - `Touch` - shape is reported, but doesn't stop the query (raycasts and sweeps
+///    only return touches in multiple-hit mode);
+///  - `Block` - shape is reported and stops the query.
+pub use physx_sys::PxQueryHitType as QueryHitType;
+
+/// Information about the shape being considered by a query filter.
+///
+/// Filter is called before the exact hit is computed, so there's no
+/// position or distance available.
+#[derive(Debug, Clone, Copy)]
+pub struct PrefilterHit<'a> {
+    /// Query filter data of the shape (see [ShapeFilterData::query_filter_data]).
+    pub filter_data: [u32; 4],
+    /// Hit flags requested by the query.
+    pub hit_flags: PxHitFlags,
+    shape: *const PxShape,
+    _marker: PhantomData<&'a PxShape>,
+}
+
+impl PrefilterHit<'_> {
+    /// Simulation filter data of the shape (see [ShapeFilterData::simulation_filter_data]),
+    /// which also holds its [CollisionLayers].
+    pub fn simulation_filter_data(&self) -> [u32; 4] {
+        // SAFETY: shape is valid while the filter is called, which the lifetime ensures
+        let data = unsafe { PxShape_getSimulationFilterData(self.shape) };
+        [data.word0, data.word1, data.word2, data.word3]
+    }
+}
+
+type QueryPredicate = dyn Fn(Entity, Entity, &PrefilterHit) -> QueryHitType + Send + Sync;
+
 pub struct SceneQueryFilter {
     filter_data: PxQueryFilterData,
     pre_filter_callback: Option<*mut PxQueryFilterCallback>, // owned
+    // double boxed to have a thin pointer to pass as userdata
+    predicate: Option<Box<Box<QueryPredicate>>>,
 }
 
+// SAFETY: callback created by `ignore` only compares actor pointers, predicates
+// are required to be Send + Sync, and userdata of raw callbacks is required to be
+// Send + Sync by the contract of unsafe `callback`, so queries can run in parallel
+unsafe impl Send for SceneQueryFilter {}
+unsafe impl Sync for SceneQueryFilter {}
+
 impl SceneQueryFilter {
     pub fn new() -> Self {
         Self::default()
@@ -52,10 +125,14 @@ impl SceneQueryFilter {
         result
     }
 
-    // false positive: https://github.com/rust-lang/rust-clippy/issues/3045
-    // userdata deref will be done in user function, this function is safe
-    #[allow(clippy::not_unsafe_ptr_arg_deref)]
-    pub fn callback(callback: RaycastHitCallback, userdata: *mut c_void) -> Self {
+    /// Filter shapes using a raw PhysX callback, see [with_predicate](Self::with_predicate)
+    /// for a safe alternative.
+    ///
+    /// # Safety
+    /// `userdata` must stay valid for as long as this filter is used, and must be
+    /// safe to access from multiple threads at once (as if it was `Send + Sync`):
+    /// filters are shared between threads, e.g. in [SpatialQuery::batch](crate::utils::spatial_query::SpatialQuery::batch).
+    pub unsafe fn callback(callback: RaycastHitCallback, userdata: *mut c_void) -> Self {
         let mut result = Self::new();
         result.filter_data.flags.insert(PxQueryFlags::Prefilter);
         result.pre_filter_callback = Some(unsafe {
@@ -64,6 +141,112 @@ impl SceneQueryFilter {
         result
     }
 
+    /// Filter shapes using a Rust closure.
+    ///
+    /// Closure receives actor entity, shape entity and shape information, and
+    /// should return [QueryHitType::Block] for shapes that should be hit,
+    /// and [QueryHitType::None] for shapes that should be ignored.
+    ///
+    /// It may be called from PhysX threads, and must not panic.
+    pub fn with_predicate(
+        predicate: impl Fn(Entity, Entity, &PrefilterHit) -> QueryHitType + Send + Sync + 'static,
+    ) -> Self {
+        unsafe extern "C" fn trampoline(
+            actor: *const PxRigidActor,
+            filter_data: *const PxFilterData,
+            shape: *const PxShape,
+            hit_flags: u32,
+            userdata: *const c_void,
+        ) -> PxQueryHitType {
+            // SAFETY: userdata is a pointer to the predicate owned by SceneQueryFilter,
+            // which outlives the query
+            let predicate = unsafe { &*(userdata as *const Box<QueryPredicate>) };
+            let filter_data = unsafe { &*filter_data };
+            let hit = PrefilterHit {
+                filter_data: [filter_data.word0, filter_data.word1, filter_data.word2, filter_data.word3],
+                hit_flags: PxHitFlags::from_bits_truncate(hit_flags as _),
+                shape,
+                _marker: PhantomData,
+            };
+
+            predicate(
+                unsafe { get_actor_entity_from_ptr(actor) },
+                unsafe { get_shape_entity_from_ptr(shape) },
+                &hit,
+            )
+        }
+
+        let predicate: Box<Box<QueryPredicate>> = Box::new(Box::new(predicate));
+        let userdata = &*predicate as *const Box<QueryPredicate> as *mut c_void;
+
+        // SAFETY: predicate is Send + Sync, and is owned by the filter
+        let mut result = unsafe { Self::callback(trampoline, userdata) };
+        result.predicate = Some(predicate);
+        result
+    }
+
+    /// Ignore listed entities, either actors or shapes.
+    pub fn excluding(entities: impl IntoIterator<Item = Entity>) -> Self {
+        let entities = entities.into_iter().collect::<EntityHashSet>();
+
+        Self::with_predicate(move |actor, shape, _| {
+            if entities.contains(&actor) || entities.contains(&shape) {
+                QueryHitType::None
+            } else {
+                QueryHitType::Block
+            }
+        })
+    }
+
+    /// Only hit shapes whose [ShapeFilterData::query_filter_data] shares at least
+    /// one bit with `mask` in any of the four words.
+    ///
+    /// This is the same check as [with_filter_data](Self::with_filter_data), but done
+    /// in a callback; prefer the latter unless you need to combine it with other filters.
+    pub fn matching_filter_data(mask: [u32; 4]) -> Self {
+        Self::with_predicate(move |_, _, hit| {
+            if hit.filter_data.iter().zip(mask.iter()).any(|(a, b)| a & b != 0) {
+                QueryHitType::Block
+            } else {
+                QueryHitType::None
+            }
+        })
+    }
+
+    /// Only hit shapes that would collide with a shape in `layers`, using the same
+    /// test as the simulation (see [CollisionLayers]).
+    ///
+    /// Shapes without [CollisionLayers] are in all layers. Layers are read from
+    /// simulation filter data, so this can be combined with [with_filter_data](Self::with_filter_data).
+    pub fn with_collision_layers(layers: CollisionLayers) -> Self {
+        let layers = [ layers.memberships, layers.filters, 0, COLLISION_LAYERS ];
+
+        Self::with_predicate(move |_, _, hit| {
+            if filter::layers_interact(layers, hit.simulation_filter_data()) {
+                QueryHitType::Block
+            } else {
+                QueryHitType::None
+            }
+        })
+    }
+
+    /// Set filter data for the query, which is matched against
+    /// [ShapeFilterData::query_filter_data] of each shape.
+    ///
+    /// If any word of query filter data is non-zero, PhysX skips shapes where
+    /// `(shape.word0 & query.word0) | (shape.word1 & query.word1) | (shape.word2 & query.word2)
+    /// | (shape.word3 & query.word3)` is zero. This check is done before any
+    /// filter callbacks, so it's cheap and composes with them.
+    ///
+    /// Typical usage is to assign a bit in `word0` for each group of shapes
+    /// (e.g. "enemies" or "walkable surfaces"), and query with a mask of groups
+    /// you're interested in, leaving other words zero.
+    pub fn with_filter_data(mut self, filter_data: [u32; 4]) -> Self {
+        let [ word0, word1, word2, word3 ] = filter_data;
+        self.filter_data.data = unsafe { PxFilterData_new_2(word0, word1, word2, word3) };
+        self
+    }
+
     pub fn without_static(mut self) -> Self {
         self.filter_data.flags.remove(PxQueryFlags::Static);
         self
@@ -80,6 +263,7 @@ impl Default for SceneQueryFilter {
         Self {
             filter_data: unsafe { PxQueryFilterData_new() },
             pre_filter_callback: None,
+            predicate: None,
         }
     }
 }
@@ -90,43 +274,162 @@ impl Drop for SceneQueryFilter {
             unsafe { PxQueryFilterCallback_delete(ptr) };
             unsafe { drop_in_place(ptr); }
         }
+        // predicate is dropped after the callback that references it
     }
 }
 
 pub trait SceneQueryExt {
+    /// Raycast returning the closest blocking hit.
     fn raycast(&self, ray: Ray3d, max_distance: f32, filter: &SceneQueryFilter) -> Option<RaycastHit>;
+
+    /// Sweep a geometry placed at `pose` along `direction`, returning the closest blocking hit.
+    fn sweep(
+        &self,
+        geometry: &bpx::Geometry,
+        pose: Transform,
+        direction: Dir3,
+        max_distance: f32,
+        filter: &SceneQueryFilter,
+    ) -> Option<SweepHit>;
+
+    /// Return all shapes overlapping a geometry placed at `pose`.
+    fn overlap(&self, geometry: &bpx::Geometry, pose: Transform, filter: &SceneQueryFilter) -> Vec<OverlapHit>;
 }
 
 impl SceneQueryExt for Scene {
     fn raycast(&self, ray: Ray3d, max_distance: f32, filter: &SceneQueryFilter) -> Option<RaycastHit> {
-        let scene = self.get();
-        let mut raycast_hit = MaybeUninit::uninit();
+        scene_raycast(&self.get(), ray, max_distance, filter)
+    }
+
+    fn sweep(
+        &self,
+        geometry: &bpx::Geometry,
+        pose: Transform,
+        direction: Dir3,
+        max_distance: f32,
+        filter: &SceneQueryFilter,
+    ) -> Option<SweepHit> {
+        scene_sweep(&self.get(), geometry, pose, direction, max_distance, filter)
+    }
+
+    fn overlap(&self, geometry: &bpx::Geometry, pose: Transform, filter: &SceneQueryFilter) -> Vec<OverlapHit> {
+        scene_overlap(&self.get(), geometry, pose, filter)
+    }
+}
 
-        if !unsafe {
-            PxSceneQueryExt_raycastSingle(
+// these functions expect caller to hold a read lock on the scene
+
+pub(crate) fn scene_raycast(
+    scene: &PxScene,
+    ray: Ray3d,
+    max_distance: f32,
+    filter: &SceneQueryFilter,
+) -> Option<RaycastHit> {
+    let mut raycast_hit = MaybeUninit::uninit();
+
+    if !unsafe {
+        PxSceneQueryExt_raycastSingle(
+            scene.as_ptr(),
+            &ray.origin.to_physx_sys(),
+            &ray.direction.to_physx_sys(),
+            max_distance,
+            PxHitFlags::Default,
+            raycast_hit.as_mut_ptr(),
+            &filter.filter_data as *const _,
+            filter.pre_filter_callback.unwrap_or(null_mut()),
+            null_mut(),
+        )
+    } { return None; }
+
+    // SAFETY: raycastSingle returned true, so we assume buffer is initialized
+    let raycast_hit = unsafe { raycast_hit.assume_init() };
+
+    Some(RaycastHit {
+        actor: unsafe { get_actor_entity_from_ptr(raycast_hit.actor) },
+        shape: unsafe { get_shape_entity_from_ptr(raycast_hit.shape) },
+        face_index: raycast_hit.faceIndex,
+        flags: raycast_hit.flags,
+        position: raycast_hit.position.to_bevy(),
+        normal: raycast_hit.normal.to_bevy(),
+        distance: raycast_hit.distance,
+    })
+}
+
+pub(crate) fn scene_sweep(
+    scene: &PxScene,
+    geometry: &bpx::Geometry,
+    pose: Transform,
+    direction: Dir3,
+    max_distance: f32,
+    filter: &SceneQueryFilter,
+) -> Option<SweepHit> {
+    let mut sweep_hit = MaybeUninit::uninit();
+
+    if !geometry.with_raw(&pose, |geometry, pose| unsafe {
+        PxSceneQueryExt_sweepSingle(
+            scene.as_ptr(),
+            geometry,
+            pose,
+            &direction.to_physx_sys(),
+            max_distance,
+            PxHitFlags::Default,
+            sweep_hit.as_mut_ptr(),
+            &filter.filter_data as *const _,
+            filter.pre_filter_callback.unwrap_or(null_mut()),
+            null_mut(),
+            0.,
+        )
+    }) { return None; }
+
+    // SAFETY: sweepSingle returned true, so we assume buffer is initialized
+    let sweep_hit = unsafe { sweep_hit.assume_init() };
+
+    Some(SweepHit {
+        actor: unsafe { get_actor_entity_from_ptr(sweep_hit.actor) },
+        shape: unsafe { get_shape_entity_from_ptr(sweep_hit.shape) },
+        face_index: sweep_hit.faceIndex,
+        flags: sweep_hit.flags,
+        position: sweep_hit.position.to_bevy(),
+        normal: sweep_hit.normal.to_bevy(),
+        distance: sweep_hit.distance,
+    })
+}
+
+pub(crate) fn scene_overlap(
+    scene: &PxScene,
+    geometry: &bpx::Geometry,
+    pose: Transform,
+    filter: &SceneQueryFilter,
+) -> Vec<OverlapHit> {
+    let mut buffer: Vec<PxOverlapHit> = Vec::with_capacity(64);
+
+    loop {
+        let count = geometry.with_raw(&pose, |geometry, pose| unsafe {
+            PxSceneQueryExt_overlapMultiple(
                 scene.as_ptr(),
-                &ray.origin.to_physx_sys(),
-                &ray.direction.to_physx_sys(),
-                max_distance,
-                PxHitFlags::Default,
-                raycast_hit.as_mut_ptr(),
+                geometry,
+                pose,
+                buffer.as_mut_ptr(),
+                buffer.capacity() as u32,
                 &filter.filter_data as *const _,
                 filter.pre_filter_callback.unwrap_or(null_mut()),
-                null_mut(),
             )
-        } { return None; }
-
-        // SAFETY: raycastSingle returned true, so we assume buffer is initialized
-        let raycast_hit = unsafe { raycast_hit.assume_init() };
-
-        Some(RaycastHit {
-            actor: unsafe { get_actor_entity_from_ptr(raycast_hit.actor) },
-            shape: unsafe { get_shape_entity_from_ptr(raycast_hit.shape) },
-            face_index: raycast_hit.faceIndex,
-            flags: raycast_hit.flags,
-            position: raycast_hit.position.to_bevy(),
-            normal: raycast_hit.normal.to_bevy(),
-            distance: raycast_hit.distance,
-        })
+        });
+
+        // -1 means buffer overflow, so we retry with a larger buffer
+        if count < 0 {
+            buffer.reserve(buffer.capacity() * 2);
+            continue;
+        }
+
+        // SAFETY: overlapMultiple has written `count` hits into the buffer
+        unsafe { buffer.set_len(count as usize) };
+        break;
     }
+
+    buffer.iter().map(|hit| OverlapHit {
+        actor: unsafe { get_actor_entity_from_ptr(hit.actor) },
+        shape: unsafe { get_shape_entity_from_ptr(hit.shape) },
+        face_index: hit.faceIndex,
+    }).collect()
 }
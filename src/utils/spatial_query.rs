@@ -0,0 +1,211 @@
+//! System parameter that runs scene queries with read-only access to the scene.
+//!
+//! [SpatialQuery] registers the same access as [`Res<Scene>`](crate::core::scene::Scene):
+//! it runs in parallel with other systems reading the scene, and conflicts with
+//! systems that take `ResMut<Scene>` (which are mostly in [PhysicsSchedule](crate::PhysicsSchedule)).
+//!
+//! The difference is in locking. With `Res<Scene>`, each query locks the scene
+//! separately (a call into PhysX when scene has `RequireRwLock` flag), while
+//! [SpatialQuery] takes a read lock once, when the system starts, and holds
+//! it until the system finishes. It also provides [SpatialQuery::batch],
+//! which runs many queries in parallel under that single lock.
+//!
+//! ### Staleness
+//!
+//! Queries see the scene as it was after the last [PhysicsSchedule](crate::PhysicsSchedule)
+//! run (see [PhysicsSet::Last](crate::PhysicsSet::Last)). Actors spawned
+//! or moved since then (e.g. by changing their `Transform`) are not visible until
+//! physics runs again.
+//!
+//! For large numbers of queries, use [SpatialQuery::batch], which splits them
+//! between threads of [ComputeTaskPool].
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_mod_physx::prelude::*;
+//! fn line_of_sight(spatial_query: SpatialQuery, query: Query<(Entity, &GlobalTransform)>) {
+//!     for (entity, transform) in query.iter() {
+//!         let ray = Ray3d::new(transform.translation(), Dir3::X);
+//!         if let Some(hit) = spatial_query.raycast(ray, 100., &default()) {
+//!             println!("{entity:?} sees {:?}", hit.actor);
+//!         }
+//!     }
+//! }
+//! ```
+use bevy::ecs::component::Tick;
+use bevy::ecs::system::{
+    ReadOnlySystemParam,
+    SystemMeta,
+    SystemParam,
+    SystemParamValidationError,
+};
+use bevy::ecs::world::unsafe_world_cell::UnsafeWorldCell;
+use bevy::prelude::*;
+use bevy::tasks::{ComputeTaskPool, TaskPool};
+
+use crate::core::scene::SceneRwLockReadGuard;
+use crate::prelude::{self as bpx, Scene};
+use crate::types::PxScene;
+use crate::utils::raycast::{
+    scene_overlap,
+    scene_raycast,
+    scene_sweep,
+    OverlapHit,
+    RaycastHit,
+    SceneQueryExt,
+    SceneQueryFilter,
+    SweepHit,
+};
+
+/// Read-only access to the physics scene for raycasts, sweeps and overlaps.
+///
+/// See [module documentation](self) for details.
+pub struct SpatialQuery<'w> {
+    scene: SceneRwLockReadGuard<'w, PxScene>,
+}
+
+/// A single query in a batch, see [SpatialQuery::batch].
+pub enum SceneQueryRequest<'a> {
+    Raycast {
+        ray: Ray3d,
+        max_distance: f32,
+    },
+    Sweep {
+        geometry: &'a bpx::Geometry,
+        pose: Transform,
+        direction: Dir3,
+        max_distance: f32,
+    },
+    Overlap {
+        geometry: &'a bpx::Geometry,
+        pose: Transform,
+    },
+}
+
+/// Result of a single query in a batch, same variant as the corresponding request.
+#[derive(Debug)]
+pub enum SceneQueryResult {
+    Raycast(Option<RaycastHit>),
+    Sweep(Option<SweepHit>),
+    Overlap(Vec<OverlapHit>),
+}
+
+// minimum amount of queries per task, so we don't spawn tasks for tiny batches
+const BATCH_MIN_CHUNK_SIZE: usize = 64;
+
+// PxScene is only read here, and physx allows concurrent reads
+// (either without simultaneous writes, or under a read lock that we hold)
+struct SceneRef<'a>(&'a PxScene);
+unsafe impl Send for SceneRef<'_> {}
+unsafe impl Sync for SceneRef<'_> {}
+
+impl SceneRef<'_> {
+    // method (instead of field access) ensures closures capture the whole wrapper
+    fn get(&self) -> &PxScene {
+        self.0
+    }
+}
+
+impl SpatialQuery<'_> {
+    /// Raw scene, locked for reading while this parameter is alive.
+    pub fn scene(&self) -> &PxScene {
+        &self.scene
+    }
+
+    /// Execute a batch of queries in parallel, all using the same filter.
+    ///
+    /// Queries are split into chunks, which are executed in [ComputeTaskPool]
+    /// under a single read lock. Results are returned in the same order as requests.
+    ///
+    /// Filter callbacks (if any) are called from multiple threads at the same time.
+    pub fn batch(&self, requests: &[SceneQueryRequest], filter: &SceneQueryFilter) -> Vec<SceneQueryResult> {
+        let scene = SceneRef(&self.scene);
+
+        let execute = |request: &SceneQueryRequest| match *request {
+            SceneQueryRequest::Raycast { ray, max_distance } => {
+                SceneQueryResult::Raycast(scene_raycast(scene.get(), ray, max_distance, filter))
+            }
+            SceneQueryRequest::Sweep { geometry, pose, direction, max_distance } => {
+                SceneQueryResult::Sweep(scene_sweep(scene.get(), geometry, pose, direction, max_distance, filter))
+            }
+            SceneQueryRequest::Overlap { geometry, pose } => {
+                SceneQueryResult::Overlap(scene_overlap(scene.get(), geometry, pose, filter))
+            }
+        };
+
+        let pool = ComputeTaskPool::get_or_init(TaskPool::default);
+        let chunk_size = requests.len()
+            .div_ceil(pool.thread_num().max(1))
+            .max(BATCH_MIN_CHUNK_SIZE);
+
+        if requests.len() <= chunk_size {
+            return requests.iter().map(execute).collect();
+        }
+
+        pool.scope(|s| {
+            for chunk in requests.chunks(chunk_size) {
+                let execute = &execute;
+                s.spawn(async move {
+                    chunk.iter().map(execute).collect::<Vec<_>>()
+                });
+            }
+        }).into_iter().flatten().collect()
+    }
+}
+
+impl SceneQueryExt for SpatialQuery<'_> {
+    fn raycast(&self, ray: Ray3d, max_distance: f32, filter: &SceneQueryFilter) -> Option<RaycastHit> {
+        scene_raycast(&self.scene, ray, max_distance, filter)
+    }
+
+    fn sweep(
+        &self,
+        geometry: &bpx::Geometry,
+        pose: Transform,
+        direction: Dir3,
+        max_distance: f32,
+        filter: &SceneQueryFilter,
+    ) -> Option<SweepHit> {
+        scene_sweep(&self.scene, geometry, pose, direction, max_distance, filter)
+    }
+
+    fn overlap(&self, geometry: &bpx::Geometry, pose: Transform, filter: &SceneQueryFilter) -> Vec<OverlapHit> {
+        scene_overlap(&self.scene, geometry, pose, filter)
+    }
+}
+
+// SAFETY: access is registered by Res<Scene>, which is read-only
+unsafe impl SystemParam for SpatialQuery<'_> {
+    type State = <Res<'static, Scene> as SystemParam>::State;
+    type Item<'w, 's> = SpatialQuery<'w>;
+
+    fn init_state(world: &mut World, system_meta: &mut SystemMeta) -> Self::State {
+        <Res<Scene> as SystemParam>::init_state(world, system_meta)
+    }
+
+    unsafe fn validate_param(
+        state: &Self::State,
+        system_meta: &SystemMeta,
+        world: UnsafeWorldCell,
+    ) -> Result<(), SystemParamValidationError> {
+        // SAFETY: forwarded from the caller
+        unsafe { <Res<Scene> as SystemParam>::validate_param(state, system_meta, world) }
+    }
+
+    unsafe fn get_param<'w, 's>(
+        state: &'s mut Self::State,
+        system_meta: &SystemMeta,
+        world: UnsafeWorldCell<'w>,
+        change_tick: Tick,
+    ) -> Self::Item<'w, 's> {
+        // SAFETY: forwarded from the caller
+        let scene = unsafe {
+            <Res<Scene> as SystemParam>::get_param(state, system_meta, world, change_tick)
+        };
+
+        SpatialQuery { scene: scene.into_inner().get() }
+    }
+}
+
+// SAFETY: only reads Scene resource
+unsafe impl ReadOnlySystemParam for SpatialQuery<'_> {}